@@ -1,6 +1,7 @@
 package handlers
 
 import (
+	"bufio"
 	"bytes"
 	"encoding/json"
 	"fmt"
@@ -15,6 +16,14 @@ import (
 
 var conversationHistory = make(map[string][]string)
 
+// pythonLLMURL renvoie l'adresse du service Flask, surchargeable par PYTHON_LLM_URL.
+func pythonLLMURL() string {
+	if url := os.Getenv("PYTHON_LLM_URL"); url != "" {
+		return url
+	}
+	return "http://localhost:5050"
+}
+
 func AskHandler(w http.ResponseWriter, r *http.Request) {
 	if r.Method != http.MethodPost {
 		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
@@ -24,12 +33,21 @@ func AskHandler(w http.ResponseWriter, r *http.Request) {
 	var req models.AskRequest
 	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
 		http.Error(w, "Invalid request", http.StatusBadRequest)
-		fmt.Printf("🟢 AskHandler - Requête reçue:\n- CharacterID: %s\n- PersonaID: %s\n- Question: %s\n",
-		req.CharacterID, req.UserID, req.Question)
+		fmt.Printf("❌ AskHandler - Requête illisible: %v\n", err)
+		return
+	}
+
+	requestID := r.Header.Get(RequestIDHeader)
+
+	// En streaming, la réponse est écrite au fil de l'eau par streamPythonLLM.
+	if req.Stream {
+		if _, err := buildPrompt(req, requestID, w); err != nil {
+			http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
+		}
 		return
 	}
 
-	answer, err := buildPrompt(req)
+	answer, err := buildPrompt(req, requestID, nil)
 	if err != nil {
 		http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
 		return
@@ -61,7 +79,7 @@ func ResetHandler(w http.ResponseWriter, r *http.Request) {
 	w.WriteHeader(http.StatusOK)
 }
 
-func buildPrompt(input models.AskRequest) (string, error) {
+func buildPrompt(input models.AskRequest, requestID string, stream http.ResponseWriter) (string, error) {
 
 	fmt.Println("📥 buildPrompt - Début traitement")
 	fmt.Printf("📌 characterID = %s, userID = %s\n", input.CharacterID, input.UserID)
@@ -81,37 +99,53 @@ func buildPrompt(input models.AskRequest) (string, error) {
 	if char == nil {
 		return "", fmt.Errorf("Character not found")
 	}
-
-	
-	personas, err := utils.LoadPersonas()
-	if err != nil {
-		return "", err
+	// Le personnage envoyé par l'application prime : elle seule développe {{char}} et
+	// {{user}} dans ses champs.
+	if input.Character != nil && input.Character.ID == char.ID {
+		char = input.Character
 	}
 
-	var persona *models.Persona
-	for _, p := range personas {
-		if p.ID == input.UserID {
-			persona = &p
-			break
+	
+	// La persona envoyée par l'application prime : le fichier est illisible quand le
+	// coffre est activé.
+	persona := input.Persona
+	if persona == nil {
+		personas, err := utils.LoadPersonas()
+		if err != nil {
+			return "", err
+		}
+		for _, p := range personas {
+			if p.ID == input.UserID {
+				persona = &p
+				break
+			}
 		}
 	}
 
-	key := fmt.Sprintf("%s_%s", input.CharacterID, input.UserID)
-	memory := utils.LoadHistory(key) 
-
-	
-	memory = append(memory, models.ChatMessage{
-		Role:    "user",
-		Content: input.Question,
-	})
+	extra := map[string]interface{}{}
+	if input.Scene != nil {
+		extra["scene"] = input.Scene
+	}
+	if input.Relationship != nil {
+		extra["relationship"] = input.Relationship
+	}
+	if input.ScenePreset != nil {
+		extra["scene_preset"] = input.ScenePreset
+	}
 
-	
-	utils.SaveHistory(key, memory)
+	// L'historique est fourni et enregistré par l'application (chiffré si le coffre est
+	// activé) : rien n'est écrit ici.
+	if input.GroupID != "" {
+		for k, v := range groupContext(input, chars) {
+			extra[k] = v
+		}
+		return callPythonLLM(input.Question, *char, persona, annotateSpeakers(input.Memory, chars), input.Model, input.Language, extra, requestID, stream)
+	}
 
-	return callPythonLLM(input.Question, *char, persona, memory, input.Model)
+	return callPythonLLM(input.Question, *char, persona, input.Memory, input.Model, input.Language, extra, requestID, stream)
 }
 
-func callPythonLLM(prompt string, character models.Character, persona *models.Persona, memory []models.ChatMessage, model string) (string, error) {
+func callPythonLLM(prompt string, character models.Character, persona *models.Persona, memory []models.ChatMessage, model string, language string, extra map[string]interface{}, requestID string, stream http.ResponseWriter) (string, error) {
 	
 	if model == "" {
 		model = "dolphin-mistral"
@@ -129,31 +163,43 @@ func callPythonLLM(prompt string, character models.Character, persona *models.Pe
     "character_background":   character.Definition,
     "user_message":           prompt,
     "memory":                 memory,
+    "language":               language,
+	}
+	if character.Personality != nil {
+		payload["personality"] = character.Personality
+	}
+	if len(character.ExampleDialogues) > 0 {
+		payload["example_dialogues"] = character.ExampleDialogues
 	}
 	if persona != nil {
 		payload["persona_id"] = persona.ID
 		payload["user_persona_name"] = persona.DisplayName
 		payload["user_persona_background"] = persona.Background
 	}
-
-
-	jsonData, err := json.Marshal(payload)
-	if err != nil {
-		return "", fmt.Errorf("échec encodage JSON: %w", err)
+	for k, v := range extra {
+		payload[k] = v
 	}
 
-	fmt.Println("📤 Requête envoyée à Flask à http://localhost:5050/generate")
-	fmt.Printf("📤 Payload: %s\n", string(jsonData))
+	if stream != nil {
+		payload["stream"] = true
+		return "", streamPythonLLM(stream, payload, requestID)
+	}
 
-	resp, err := http.Post("http://localhost:5050/generate", "application/json", bytes.NewBuffer(jsonData))
+	resp, err := postToPython("/generate", payload, requestID)
 	if err != nil {
-		fmt.Printf("❌ Erreur HTTP: %v\n", err)
-		return "", fmt.Errorf("requête HTTP échouée: %w", err)
+		return "", err
 	}
+	return readPythonAnswer(resp)
+}
+
+// readPythonAnswer extrait le champ `response` d'une réponse de /generate.
+func readPythonAnswer(resp *http.Response) (string, error) {
 	defer resp.Body.Close()
 
 	body, _ := io.ReadAll(resp.Body)
-	fmt.Printf("📥 Réponse Flask (Status %d): %s\n", resp.StatusCode, string(body))
+	// Ni le contenu des requêtes ni les réponses du modèle ne sont journalisés : la sortie
+	// du service finit dans data/logs.
+	fmt.Printf("📥 Réponse Flask (Status %d, %d octets)\n", resp.StatusCode, len(body))
 
 	if resp.StatusCode != 200 {
 		return "", fmt.Errorf("erreur HTTP %d: %s", resp.StatusCode, string(body))
@@ -166,17 +212,73 @@ func callPythonLLM(prompt string, character models.Character, persona *models.Pe
 	}
 
 	if status, ok := result["status"].(string); ok && status != "success" {
-		fmt.Println("❌ Réponse avec erreur de Flask:")
-		fmt.Println(string(body))
+		fmt.Println("❌ Réponse avec erreur de Flask")
 		return fmt.Sprintf("[Erreur modèle: %v]", result["response"]), nil
 	}
 
 	response, ok := result["response"].(string)
 	if !ok {
-		fmt.Printf("❌ Pas de field 'response' dans: %v\n", result)
+		fmt.Println("❌ Pas de field 'response' dans la réponse de Flask")
 		return "", fmt.Errorf("réponse invalide: pas de field 'response'")
 	}
 
-	fmt.Printf("✅ Réponse reçue: %s\n", response)
+	fmt.Printf("✅ Réponse reçue (%d octets)\n", len(response))
 	return response, nil
 }
+
+func postToPython(path string, payload map[string]interface{}, requestID string) (*http.Response, error) {
+	jsonData, err := json.Marshal(payload)
+	if err != nil {
+		return nil, fmt.Errorf("échec encodage JSON: %w", err)
+	}
+
+	targetURL := pythonLLMURL() + path
+	fmt.Println("📤 Requête envoyée à Flask à " + targetURL)
+	fmt.Printf("📤 Payload: %d octets\n", len(jsonData))
+
+	req, err := http.NewRequest(http.MethodPost, targetURL, bytes.NewBuffer(jsonData))
+	if err != nil {
+		return nil, fmt.Errorf("requête HTTP invalide: %w", err)
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if requestID != "" {
+		req.Header.Set(RequestIDHeader, requestID)
+	}
+
+	resp, err := http.DefaultClient.Do(req)
+	if err != nil {
+		fmt.Printf("❌ Erreur HTTP: %v\n", err)
+		return nil, fmt.Errorf("requête HTTP échouée: %w", err)
+	}
+	return resp, nil
+}
+
+// streamPythonLLM relaie ligne à ligne le flux NDJSON de Flask. Une erreur n'est renvoyée
+// que tant que rien n'a été écrit dans w.
+func streamPythonLLM(w http.ResponseWriter, payload map[string]interface{}, requestID string) error {
+	resp, err := postToPython("/generate", payload, requestID)
+	if err != nil {
+		return err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		body, _ := io.ReadAll(resp.Body)
+		return fmt.Errorf("erreur HTTP %d: %s", resp.StatusCode, string(body))
+	}
+
+	w.Header().Set("Content-Type", "application/x-ndjson")
+	flusher, _ := w.(http.Flusher)
+
+	scanner := bufio.NewScanner(resp.Body)
+	for scanner.Scan() {
+		w.Write(append(scanner.Bytes(), '\n'))
+		if flusher != nil {
+			flusher.Flush()
+		}
+	}
+	if err := scanner.Err(); err != nil {
+		fmt.Printf("❌ [%s] Flux interrompu: %v\n", requestID, err)
+	}
+	return nil
+}
@@ -0,0 +1,92 @@
+package handlers
+
+import (
+	"encoding/json"
+	"io"
+	"net/http"
+
+	"go-api/models"
+	"go-api/utils"
+)
+
+// NextSpeakerHandler demande au service Python lequel des personnages du groupe doit
+// répondre. La réponse est {"speaker_id": "..."} ou {"speaker_id": null}.
+func NextSpeakerHandler(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var req models.NextSpeakerRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		http.Error(w, "Invalid request", http.StatusBadRequest)
+		return
+	}
+
+	chars, err := utils.LoadCharacters()
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	payload := map[string]interface{}{
+		"model":        req.Model,
+		"members":      memberSummaries(req.CharacterIDs, chars),
+		"memory":       annotateSpeakers(req.Memory, chars),
+		"user_message": req.Question,
+		"language":     req.Language,
+	}
+
+	resp, err := postToPython("/next-speaker", payload, r.Header.Get(RequestIDHeader))
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusBadGateway)
+		return
+	}
+	defer resp.Body.Close()
+
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(resp.StatusCode)
+	io.Copy(w, resp.Body)
+}
+
+// groupContext ajoute au payload Flask les autres personnages présents dans la scène.
+func groupContext(input models.AskRequest, chars []models.Character) map[string]interface{} {
+	return map[string]interface{}{
+		"group_id":      input.GroupID,
+		"group_members": memberSummaries(input.Members, chars),
+	}
+}
+
+func memberSummaries(ids []string, chars []models.Character) []map[string]string {
+	members := []map[string]string{}
+	for _, id := range ids {
+		for _, c := range chars {
+			if c.ID == id {
+				members = append(members, map[string]string{
+					"id":          c.ID,
+					"name":        c.Name,
+					"personality": c.Tagline,
+					"description": c.Description,
+					"background":  c.Definition,
+				})
+				break
+			}
+		}
+	}
+	return members
+}
+
+// annotateSpeakers renseigne le nom de l'intervenant de chaque réplique.
+func annotateSpeakers(memory []models.ChatMessage, chars []models.Character) []models.ChatMessage {
+	names := make(map[string]string, len(chars))
+	for _, c := range chars {
+		names[c.ID] = c.Name
+	}
+
+	annotated := make([]models.ChatMessage, len(memory))
+	for i, msg := range memory {
+		msg.SpeakerName = names[msg.SpeakerID]
+		annotated[i] = msg
+	}
+	return annotated
+}
@@ -6,7 +6,10 @@ import (
 )
 
 
+// Version du service, renvoyée par /health.
+const Version = "0.1.0"
+
 func HealthHandler(w http.ResponseWriter, r *http.Request) {
 	w.WriteHeader(http.StatusOK)
-	json.NewEncoder(w).Encode(map[string]string{"status": "ok"})
+	json.NewEncoder(w).Encode(map[string]string{"status": "ok", "service": "PersonAI Go API", "version": Version})
 }
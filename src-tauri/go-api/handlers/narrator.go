@@ -0,0 +1,80 @@
+package handlers
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"go-api/models"
+	"go-api/utils"
+)
+
+// NarrateHandler fait écrire au service Python une intervention du narrateur, qui se
+// termine par l'état de la scène mis à jour. L'application enregistre elle-même le message.
+func NarrateHandler(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var req models.NarrateRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		http.Error(w, "Invalid request", http.StatusBadRequest)
+		return
+	}
+
+	chars, err := utils.LoadCharacters()
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	characters := memberSummaries(req.CharacterIDs, chars)
+	memory := annotateSpeakers(req.Memory, chars)
+
+	// Conversation à deux : les répliques non attribuées sont celles du personnage.
+	if len(characters) == 1 {
+		for i := range memory {
+			if memory[i].Role == "assistant" && memory[i].SpeakerName == "" {
+				memory[i].SpeakerName = characters[0]["name"]
+			}
+		}
+	}
+
+	payload := map[string]interface{}{
+		"type":         "narrator",
+		"model":        req.Model,
+		"characters":   characters,
+		"scene":        req.Scene,
+		"scene_preset": req.ScenePreset,
+		"memory":       memory,
+		"user_message": req.Direction,
+		"language":     req.Language,
+	}
+
+	if req.Persona != nil {
+		payload["user_persona_name"] = req.Persona.DisplayName
+		payload["user_persona_background"] = req.Persona.Background
+	} else if personas, err := utils.LoadPersonas(); err == nil {
+		for _, p := range personas {
+			if p.ID == req.PersonaID {
+				payload["user_persona_name"] = p.DisplayName
+				payload["user_persona_background"] = p.Background
+				break
+			}
+		}
+	}
+
+	resp, err := postToPython("/generate", payload, r.Header.Get(RequestIDHeader))
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusBadGateway)
+		return
+	}
+
+	answer, err := readPythonAnswer(resp)
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	json.NewEncoder(w).Encode(models.AskResponse{Answer: answer})
+}
@@ -0,0 +1,21 @@
+package handlers
+
+import (
+	"log"
+	"net/http"
+)
+
+// RequestIDHeader suit une requête de bout en bout : l'application le génère, l'API le
+// journalise et le transmet au service Python.
+const RequestIDHeader = "X-Request-Id"
+
+// WithRequestID journalise la requête avec son identifiant et le renvoie dans la réponse.
+func WithRequestID(next http.HandlerFunc) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		if id := r.Header.Get(RequestIDHeader); id != "" {
+			w.Header().Set(RequestIDHeader, id)
+			log.Printf("[%s] %s %s", id, r.Method, r.URL.Path)
+		}
+		next(w, r)
+	}
+}
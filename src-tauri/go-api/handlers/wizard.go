@@ -0,0 +1,45 @@
+package handlers
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"go-api/models"
+)
+
+// CharacterDraftHandler demande au service Python un personnage au format JSON. La
+// réponse est renvoyée telle quelle : l'application la valide et redemande si besoin.
+func CharacterDraftHandler(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var req models.CharacterDraftRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		http.Error(w, "Invalid request", http.StatusBadRequest)
+		return
+	}
+
+	payload := map[string]interface{}{
+		"model":          req.Model,
+		"concept":        req.Concept,
+		"tags":           req.Tags,
+		"language":       req.Language,
+		"previous_error": req.PreviousError,
+	}
+
+	resp, err := postToPython("/character-draft", payload, r.Header.Get(RequestIDHeader))
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusBadGateway)
+		return
+	}
+
+	answer, err := readPythonAnswer(resp)
+	if err != nil {
+		http.Error(w, "Service Error : "+err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	json.NewEncoder(w).Encode(models.AskResponse{Answer: answer})
+}
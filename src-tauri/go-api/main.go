@@ -3,23 +3,32 @@ package main
 import (
 	"log"
 	"net/http"
+	"os"
 
 	"go-api/handlers"
 )
 
 func main() {
 	http.HandleFunc("/health", handlers.HealthHandler)
-	http.HandleFunc("/save-character", handlers.SaveCharacterHandler)
-	http.HandleFunc("/delete-character", handlers.DeleteCharacterHandler)
-	http.HandleFunc("/save-persona", handlers.SavePersonaHandler)
-	http.HandleFunc("/delete-persona", handlers.DeletePersonaHandler)
-	http.HandleFunc("/personas", handlers.ListPersonasHandler)
-	http.HandleFunc("/ask", handlers.AskHandler)
-	http.HandleFunc("/reset", handlers.ResetHandler)
-	http.HandleFunc("/debug/paths", handlers.DebugPathsHandler)
+	http.HandleFunc("/save-character", handlers.WithRequestID(handlers.SaveCharacterHandler))
+	http.HandleFunc("/delete-character", handlers.WithRequestID(handlers.DeleteCharacterHandler))
+	http.HandleFunc("/save-persona", handlers.WithRequestID(handlers.SavePersonaHandler))
+	http.HandleFunc("/delete-persona", handlers.WithRequestID(handlers.DeletePersonaHandler))
+	http.HandleFunc("/personas", handlers.WithRequestID(handlers.ListPersonasHandler))
+	http.HandleFunc("/ask", handlers.WithRequestID(handlers.AskHandler))
+	http.HandleFunc("/reset", handlers.WithRequestID(handlers.ResetHandler))
+	http.HandleFunc("/next-speaker", handlers.WithRequestID(handlers.NextSpeakerHandler))
+	http.HandleFunc("/narrate", handlers.WithRequestID(handlers.NarrateHandler))
+	http.HandleFunc("/character-draft", handlers.WithRequestID(handlers.CharacterDraftHandler))
+	http.HandleFunc("/debug/paths", handlers.WithRequestID(handlers.DebugPathsHandler))
 
 
 
-	log.Println("\U0001F680 Go API en écoute sur :8080")
-	log.Fatal(http.ListenAndServe(":8080", nil))
+	port := os.Getenv("PORT")
+	if port == "" {
+		port = "8080"
+	}
+
+	log.Println("\U0001F680 Go API en écoute sur :" + port)
+	log.Fatal(http.ListenAndServe(":"+port, nil))
 }
\ No newline at end of file
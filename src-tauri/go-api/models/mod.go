@@ -1,14 +1,33 @@
 package models
 
 type Character struct {
-	ID          string              `json:"id"`
-	Name        string              `json:"name"`
-	Tagline     string              `json:"tagline"`
-	Description string              `json:"description"`
-	Greeting    string              `json:"greeting"`
-	Definition  string              `json:"definition"`
-	Tags        map[string][]string `json:"tags"`
-	Img         string              `json:"img"`
+	ID                 string              `json:"id"`
+	Name               string              `json:"name"`
+	Tagline            string              `json:"tagline"`
+	Description        string              `json:"description"`
+	Greeting           string              `json:"greeting"`
+	Definition         string              `json:"definition"`
+	Tags               map[string][]string `json:"tags"`
+	Img                string              `json:"img"`
+	AlternateGreetings []string            `json:"alternate_greetings,omitempty"`
+	ExampleDialogues   []ExampleDialogue   `json:"example_dialogues,omitempty"`
+	Personality        *Personality        `json:"personality,omitempty"`
+	DefaultPersonaID   *string             `json:"default_persona_id,omitempty"`
+}
+
+type ExampleDialogue struct {
+	User      string `json:"user"`
+	Character string `json:"character"`
+}
+
+// Personality : curseurs de 0 à 100 réglés dans l'application.
+type Personality struct {
+	Warmth      int `json:"warmth"`
+	Verbosity   int `json:"verbosity"`
+	Formality   int `json:"formality"`
+	Humor       int `json:"humor"`
+	Aggression  int `json:"aggression"`
+	MemoryDepth int `json:"memory_depth"`
 }
 
 type Persona struct {
@@ -26,6 +45,68 @@ type AskRequest struct {
 	Style       string `json:"style"`
 	UserPersona string `json:"user_persona"`
 	Model       string `json:"model"`
+	Language    string `json:"language"`
+	Stream      bool   `json:"stream"`
+
+	// Conversation de groupe : l'historique est fourni par l'application.
+	GroupID string        `json:"group_id"`
+	Members []string      `json:"members"`
+	Memory  []ChatMessage `json:"memory"`
+
+	Scene        *SceneState        `json:"scene"`
+	Relationship *RelationshipState `json:"relationship"`
+	ScenePreset  *ScenePreset       `json:"scene_preset"`
+	Persona      *Persona           `json:"persona"`
+
+	// Personnage qui répond, macros déjà développées par l'application.
+	Character *Character `json:"character"`
+}
+
+// ScenePreset vient du catalogue de l'application ; Sampling ne contient que les options
+// Ollama à remplacer.
+type ScenePreset struct {
+	ID       string             `json:"id"`
+	Name     string             `json:"name"`
+	Setting  string             `json:"setting"`
+	Tone     string             `json:"tone"`
+	Focus    string             `json:"focus"`
+	Sampling map[string]float64 `json:"sampling"`
+}
+
+// RelationshipState est tenu par l'application ; il est transmis tel quel au service Python.
+type RelationshipState struct {
+	Mood     string `json:"mood"`
+	Energy   string `json:"energy"`
+	Affinity int    `json:"affinity"`
+	Trust    int    `json:"trust"`
+	Tension  int    `json:"tension"`
+}
+
+type SceneState struct {
+	Location          string   `json:"location"`
+	TimeOfDay         string   `json:"time_of_day"`
+	PresentCharacters []string `json:"present_characters"`
+	Objectives        []string `json:"objectives"`
+}
+
+type NarrateRequest struct {
+	CharacterIDs []string      `json:"character_ids"`
+	PersonaID    string        `json:"persona_id"`
+	Direction    string        `json:"direction"`
+	Scene        SceneState    `json:"scene"`
+	ScenePreset  *ScenePreset  `json:"scene_preset"`
+	Persona      *Persona      `json:"persona"`
+	Memory       []ChatMessage `json:"memory"`
+	Model        string        `json:"model"`
+	Language     string        `json:"language"`
+}
+
+type NextSpeakerRequest struct {
+	CharacterIDs []string      `json:"character_ids"`
+	Question     string        `json:"question"`
+	Memory       []ChatMessage `json:"memory"`
+	Model        string        `json:"model"`
+	Language     string        `json:"language"`
 }
 
 type AskResponse struct {
@@ -33,6 +114,16 @@ type AskResponse struct {
 }
 
 type ChatMessage struct {
-	Role    string `json:"role"`
-	Content string `json:"content"`
+	Role        string `json:"role"`
+	Content     string `json:"content"`
+	SpeakerID   string `json:"speaker_id,omitempty"`
+	SpeakerName string `json:"speaker_name,omitempty"`
+}
+
+type CharacterDraftRequest struct {
+	Concept       string   `json:"concept"`
+	Tags          []string `json:"tags"`
+	Model         string   `json:"model"`
+	Language      string   `json:"language"`
+	PreviousError string   `json:"previous_error"`
 }
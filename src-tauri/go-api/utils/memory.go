@@ -1,40 +1,13 @@
 package utils
 
 import (
-	"encoding/json"
-	"fmt"
-	"go-api/models"
 	"os"
 	"path/filepath"
 )
 
+// L'historique des conversations est lu et écrit par l'application uniquement ; le service
+// ne fait que supprimer celui d'un personnage supprimé.
 const historyDir = "data/history"
-const memoryLimit = 5
-
-
-func LoadHistory(key string) []models.ChatMessage {
-	path := fmt.Sprintf("data/history/%s.json", key)
-	data, err := os.ReadFile(path)
-	if err != nil {
-		return []models.ChatMessage{}
-	}
-
-	var messages []models.ChatMessage
-	if err := json.Unmarshal(data, &messages); err != nil {
-		return []models.ChatMessage{}
-	}
-	return messages
-}
-
-func SaveHistory(key string, messages []models.ChatMessage) error {
-	path := fmt.Sprintf("data/history/%s.json", key)
-	data, err := json.MarshalIndent(messages, "", "  ")
-	if err != nil {
-		return err
-	}
-	return os.WriteFile(path, data, 0644)
-}
-
 
 func DeleteAllHistoryForCharacter(charID string) {
 	entries, err := os.ReadDir(historyDir)
@@ -1,8 +1,7 @@
-from flask import Flask, request, jsonify
+from flask import Flask, Response, request, jsonify
 import requests
 import json
 import os
-from datetime import datetime
 from flask import Flask
 from flask_cors import CORS
 
@@ -10,9 +9,24 @@ app = Flask(__name__)
 CORS(app)
 
 MODEL_NAME = "dolphin-mistral:latest"
-OLLAMA_URL = "http://localhost:11434/api/generate"
+SERVICE_VERSION = "0.1.0"
+OLLAMA_URL = os.environ.get("OLLAMA_URL", "http://localhost:11434") + "/api/generate"
+PORT = int(os.environ.get("PORT", "5050"))
+
+# Langue de réponse demandée par l'application (réglage `locale`).
+REPLY_LANGUAGES = {
+    "fr": "French",
+    "en": "English",
+}
+DEFAULT_LANGUAGE = "fr"
+
+# Message transmis quand l'utilisateur laisse la scène de groupe se poursuivre sans rien dire.
+GROUP_CONTINUE_MESSAGE = "(The user stays silent and lets the scene continue.)"
 
 def format_prompt_for_character(prompt_data):
+    if prompt_data.get('group_id') and not prompt_data.get('user_message'):
+        prompt_data = {**prompt_data, 'user_message': GROUP_CONTINUE_MESSAGE}
+
     character_prompt = f"""### ADVANCED CHARACTER ROLEPLAY SYSTEM !###
 
 You are now embodying {prompt_data.get('character_name', 'a character')} in a detailed, immersive roleplay scenario.
@@ -22,15 +36,15 @@ Name: {prompt_data.get('character_name', 'Unknown')}
 Description: {prompt_data.get('character_description', 'No description')}
 Personality: {prompt_data.get('character_personality', 'Complex individual')}
 Background: {prompt_data.get('character_background', 'Rich history')}
-
+{format_personality_directives(prompt_data.get('personality'))}{format_persona_context(prompt_data)}{format_example_dialogues(prompt_data)}{format_group_context(prompt_data)}{format_scene_context(prompt_data.get('scene'))}
 **CONVERSATION HISTORY:**
-{format_memory_context(prompt_data.get('memory', []))}
+{format_group_memory(prompt_data) if prompt_data.get('group_id') else format_memory_context(prompt_data.get('memory', []), memory_depth(prompt_data, 5))}
 
 **TAGS / GENRE / CONTEXTUAL HINTS:**
 {", ".join(prompt_data.get('tags', [])) if prompt_data.get('tags') else "No tags provided"}
 
 **CURRENT SITUATION:**
-User's message: "{prompt_data.get('user_message', '')}"
+{prompt_data.get('user_persona_name') or 'User'}'s message: "{prompt_data.get('user_message', '')}"
 
 **ADVANCED ROLEPLAY INSTRUCTIONS:**
 
@@ -84,25 +98,227 @@ User's message: "{prompt_data.get('user_message', '')}"
 *[Additional scene description or reaction]*
 
 **RESPONSE GUIDELINES:**
-- Length: Max 3–4 lines (around 40–60 words)
+- Length: {format_length_guideline(prompt_data.get('personality'))}
 - Tone: Match character's personality and current emotional state
 - Focus: Balance action, dialogue, and description
 - Engagement: Create opportunities for meaningful interaction
 
+{format_language_instruction(prompt_data.get('language'))}
+
 **NOW RESPOND AS {prompt_data.get('character_name', 'YOUR CHARACTER')}:**"""
 
     return character_prompt
 
-def format_memory_context(memory):
+def format_persona_context(prompt_data):
+    """La persona incarnée par l'utilisateur : son nom et ce qu'on sait d'elle."""
+    name = prompt_data.get('user_persona_name')
+    if not name:
+        return ""
+
+    background = prompt_data.get('user_persona_background') or "Nothing more is known about them yet."
+    return f"""
+**WHO YOU ARE TALKING TO:**
+Name: {name}
+About them: {background}
+- Address them as {name} and take their background into account
+- Never speak or act on {name}'s behalf
+"""
+
+def format_language_instruction(language):
+    reply_language = REPLY_LANGUAGES.get(language or DEFAULT_LANGUAGE, REPLY_LANGUAGES[DEFAULT_LANGUAGE])
+    return f"""**LANGUAGE:**
+- Always write your reply in {reply_language}, including narration and actions
+- Keep names and proper nouns unchanged"""
+
+# Consignes des curseurs de personnalité : (axe, consigne sous 35, consigne au-dessus de 65).
+PERSONALITY_AXES = [
+    ("warmth", "Stay cool and distant; affection has to be earned", "Be openly warm, caring and encouraging"),
+    ("verbosity", "Keep replies terse: one or two short lines", "Take your time: richer descriptions and longer replies are welcome"),
+    ("formality", "Speak casually, with slang and contractions", "Speak formally and politely, choosing words with care"),
+    ("humor", "Stay serious; jokes are rare and dry", "Be playful and witty; tease and joke often"),
+    ("aggression", "Avoid confrontation; stay gentle even when provoked", "Be confrontational and quick to anger; push back hard"),
+]
+
+def format_personality_directives(personality):
+    """Traduit les curseurs (0 à 100) en consignes ; un curseur proche de 50 n'en donne pas."""
+    if not personality:
+        return ""
+
+    directives = []
+    for axis, low, high in PERSONALITY_AXES:
+        value = personality.get(axis, 50)
+        if value <= 35:
+            directives.append(f"- {low}")
+        elif value >= 65:
+            directives.append(f"- {high}")
+    if not directives:
+        return ""
+
+    return "\n**PERSONALITY TRAITS:**\n" + "\n".join(directives) + "\n"
+
+def personality_sampling(personality):
+    """Réglages Ollama tirés des curseurs : la prolixité allonge les réponses, l'humour les
+    rend moins prévisibles. À 50, on retrouve les valeurs par défaut."""
+    if not personality:
+        return {}
+
+    return {
+        "num_predict": int(40 + personality.get("verbosity", 50) * 1.2),
+        "temperature": round(0.6 + personality.get("humor", 50) * 0.002, 2),
+    }
+
+def format_length_guideline(personality):
+    """Longueur visée, accordée à `num_predict` : sans curseurs, la consigne historique ;
+    sinon elle suit la prolixité (environ 0,75 mot par jeton)."""
+    if not personality:
+        return "Max 3–4 lines (around 40–60 words)"
+
+    words = int(personality_sampling(personality)["num_predict"] * 0.75)
+    return f"Around {max(words - 15, 15)}–{words} words"
+
+# Part du contexte (num_ctx 4096) réservée aux dialogues d'exemple, en caractères.
+EXAMPLE_DIALOGUE_BUDGET = 1500
+
+def format_example_dialogues(prompt_data):
+    """Dialogues d'exemple dans l'ordre, tant qu'ils tiennent dans le budget ; ceux qui
+    débordent sont laissés de côté."""
+    dialogues = prompt_data.get('example_dialogues') or []
+    user_name = prompt_data.get('user_persona_name') or "User"
+    name = prompt_data.get('character_name', 'Character')
+
+    examples = []
+    used = 0
+    for dialogue in dialogues:
+        example = f"{user_name}: {dialogue.get('user', '').strip()}\n{name}: {dialogue.get('character', '').strip()}"
+        if used + len(example) > EXAMPLE_DIALOGUE_BUDGET:
+            break
+        examples.append(example)
+        used += len(example)
+    if not examples:
+        return ""
+
+    return f"""
+**EXAMPLE DIALOGUES:**
+These show how {name} speaks; imitate the voice and style, not the content.
+
+""" + "\n\n".join(examples) + "\n"
+
+def memory_depth(prompt_data, default):
+    depth = (prompt_data.get('personality') or {}).get('memory_depth')
+    return depth if isinstance(depth, int) and depth > 0 else default
+
+def format_group_context(prompt_data):
+    members = prompt_data.get('group_members') or []
+    if not members:
+        return ""
+
+    name = prompt_data.get('character_name', 'your character')
+    profiles = "\n".join(
+        f"- {m.get('name', 'Unknown')}: {m.get('personality', '')}. {m.get('description', '')} {m.get('background', '')}".strip()
+        for m in members
+    )
+    return f"""
+**OTHER CHARACTERS IN THE SCENE:**
+{profiles}
+
+**GROUP SCENE RULES:**
+- Speak and act only as {name}; never write dialogue or actions for the other characters
+- React to what the other characters just said or did when it matters to {name}
+- Address other characters by name when speaking to them
+"""
+
+def format_group_memory(prompt_data):
+    memory = prompt_data.get('memory') or []
+    if not memory:
+        return "This is the beginning of the scene."
+
+    user_name = prompt_data.get('user_persona_name') or "User"
+    lines = []
+    for msg in memory[-memory_depth(prompt_data, 10):]:
+        if msg.get('role') == 'user':
+            speaker = user_name
+        elif msg.get('role') == 'narrator':
+            speaker = "Narrator"
+        else:
+            speaker = msg.get('speaker_name') or prompt_data.get('character_name') or "Someone"
+        lines.append(f"• {speaker}: \"{msg.get('content', '')}\"")
+    return "Recent exchanges:\n" + "\n".join(lines)
+
+def format_scene_context(scene):
+    if not scene:
+        return ""
+
+    fields = [
+        ("Location", scene.get('location')),
+        ("Time of day", scene.get('time_of_day')),
+        ("Present", "; ".join(scene.get('present_characters') or [])),
+        ("Objectives", "; ".join(scene.get('objectives') or [])),
+    ]
+    lines = "\n".join(f"{label}: {value}" for label, value in fields if value)
+    if not lines:
+        return ""
+
+    return f"""
+**CURRENT SCENE:**
+{lines}
+- Stay consistent with this setting; do not contradict it
+"""
+
+def format_narrator_prompt(prompt_data):
+    user_name = prompt_data.get('user_persona_name') or "the user"
+    characters = prompt_data.get('characters') or []
+    profiles = "\n".join(
+        f"- {c.get('name', 'Unknown')}: {c.get('personality', '')}. {c.get('description', '')}".strip()
+        for c in characters
+    ) or "- None"
+    direction = prompt_data.get('user_message') or "Move the story forward."
+    scene = prompt_data.get('scene') or {}
+
+    return f"""### NARRATOR / GAME MASTER ###
+
+You are the omniscient narrator of an interactive story. You describe the world, the passing of time and the actions of minor non-player characters.
+
+**MAIN CHARACTERS (never speak or act for them):**
+{profiles}
+
+**PLAYER:** {user_name} — {prompt_data.get('user_persona_background') or 'no background given'}
+Never decide what {user_name} says, feels or does.
+{format_scene_context(scene)}
+**STORY SO FAR:**
+{format_group_memory({**prompt_data, "group_id": True})}
+
+**DIRECTION FROM THE PLAYER:**
+"{direction}"
+{format_scene_preset(prompt_data.get('scene_preset'))}
+**NARRATION INSTRUCTIONS:**
+- Write 3 to 6 sentences in present tense, with sensory details
+- Minor characters may speak, in quotes
+- End on a hook that invites the characters or the player to react
+- Then, on separate lines, give the updated scene state exactly in this format:
+[SCENE]
+location: <where the scene takes place>
+time: <time of day>
+present: <names separated by ;>
+objectives: <current goals separated by ;>
+[/SCENE]
+
+{format_language_instruction(prompt_data.get('language'))}
+
+**NARRATE:**"""
+
+def format_memory_context(memory, depth=5):
     if not memory:
         return "This is the beginning of your interaction."
     
     formatted_memory = "Previous conversation highlights:\n"
-    for i, msg in enumerate(memory[-5:]):  
-        if msg.get('type') == 'user':
+    for i, msg in enumerate(memory[-depth:]):  
+        role = msg.get('role') or msg.get('type')
+        if role == 'user':
             formatted_memory += f"• User said: \"{msg.get('content', '')}\"\n"
-        elif msg.get('type') == 'ai':
+        elif role in ('assistant', 'ai'):
             formatted_memory += f"• You responded: \"{msg.get('content', '')[:100]}...\"\n"
+        elif role == 'narrator':
+            formatted_memory += f"• Narrator: \"{msg.get('content', '')[:100]}...\"\n"
     
     return formatted_memory
 
@@ -127,37 +343,57 @@ Scene Tension: {mood_context.get('tension', 'comfortable')}
     
     return base_prompt
 
-def create_scene_prompt(prompt_data, scene_type="casual"):
-    scene_templates = {
-        "casual": {
-            "setting": "Create a relaxed, everyday environment",
-            "tone": "Friendly and approachable",
-            "focus": "Natural conversation and character development"
-        },
-        "dramatic": {
-            "setting": "Build tension and emotional intensity",
-            "tone": "Heightened emotions and meaningful exchanges",
-            "focus": "Character depth and emotional revelation"
-        },
-        "intimate": {
-            "setting": "Create a private, personal atmosphere",
-            "tone": "Warm, connected, and emotionally open",
-            "focus": "Emotional intimacy and vulnerability"
-        },
-        "action": {
-            "setting": "Dynamic environment with movement and activity",
-            "tone": "Energetic and engaging",
-            "focus": "Physical actions and reactive dialogue"
-        }
+def mood_context_from_relationship(relationship):
+    """Traduit l'état numérique tenu par l'application en indications pour le prompt."""
+    if not relationship:
+        return None
+
+    affinity = relationship.get('affinity', 0)
+    trust = relationship.get('trust', 50)
+    tension = relationship.get('tension', 0)
+
+    if affinity <= -50:
+        bond = "hostile"
+    elif affinity < -15:
+        bond = "cold and wary"
+    elif affinity < 15:
+        bond = "getting to know each other"
+    elif affinity < 50:
+        bond = "friendly"
+    else:
+        bond = "deeply attached"
+
+    if trust < 30:
+        bond += ", distrustful"
+    elif trust > 70:
+        bond += ", trusting"
+
+    if tension < 20:
+        tension_label = "comfortable"
+    elif tension < 50:
+        tension_label = "slightly tense"
+    elif tension < 80:
+        tension_label = "tense"
+    else:
+        tension_label = "on the verge of conflict"
+
+    return {
+        "mood": relationship.get('mood', 'neutral'),
+        "energy": relationship.get('energy', 'moderate'),
+        "relationship_state": bond,
+        "tension": tension_label,
     }
-    
-    scene_config = scene_templates.get(scene_type, scene_templates["casual"])
-    
-    scene_prompt = f"""
+
+def format_scene_preset(scene_config):
+    """Ambiance choisie dans le catalogue de préréglages de l'application."""
+    if not scene_config:
+        return ""
+
+    return f"""
 **SCENE CONFIGURATION:**
-Setting Style: {scene_config['setting']}
-Tone: {scene_config['tone']}
-Primary Focus: {scene_config['focus']}
+Setting Style: {scene_config.get('setting', '')}
+Tone: {scene_config.get('tone', '')}
+Primary Focus: {scene_config.get('focus', '')}
 
 **SCENE-SPECIFIC INSTRUCTIONS:**
 - Adapt your response style to match the scene type
@@ -165,16 +401,23 @@ Primary Focus: {scene_config['focus']}
 - Maintain scene consistency throughout the interaction
 - Create immersive experiences that draw the user in
 """
-    
-    base_prompt = format_prompt_for_character(prompt_data)
-    return base_prompt + scene_prompt
+
+# Options Ollama qu'un préréglage de scène peut remplacer.
+SAMPLING_OPTIONS = {"temperature": float, "top_p": float, "top_k": int, "repeat_penalty": float, "num_predict": int}
+
+def apply_sampling_overrides(options, scene_config):
+    sampling = (scene_config or {}).get("sampling") or {}
+    for name, cast in SAMPLING_OPTIONS.items():
+        if sampling.get(name) is not None:
+            options[name] = cast(sampling[name])
 
 @app.route("/generate", methods=["POST"])
 def generate():
     data = request.get_json()
+    request_id = request.headers.get("X-Request-Id", "-")
 
-    print("📨 Reçu du backend Go :")
-    print(json.dumps(data, indent=2))
+    # Seulement les noms des champs : prompts et historique ne doivent pas finir dans les journaux.
+    print(f"📨 [{request_id}] Reçu du backend Go : {', '.join(sorted(data or {}))}")
 
     if not data:
         print("❌ No data received")
@@ -182,7 +425,10 @@ def generate():
 
    
     if data.get("type") == "character":
-        formatted_prompt = format_prompt_for_character(data)
+        formatted_prompt = enhance_character_prompt_with_mood(data, mood_context_from_relationship(data.get("relationship")))
+        formatted_prompt += format_scene_preset(data.get("scene_preset"))
+    elif data.get("type") == "narrator":
+        formatted_prompt = format_narrator_prompt(data)
     else:
         formatted_prompt = data.get("prompt", "")
 
@@ -190,7 +436,7 @@ def generate():
 
     try:
         ollama_payload = {
-            "model": MODEL_NAME,
+            "model": data.get("model") or MODEL_NAME,
             "prompt": formatted_prompt,
             "stream": False,
             "options": {
@@ -203,7 +449,19 @@ def generate():
             }
         }
 
-        print(f"🚀 Sending to Ollama...")
+        # Le narrateur décrit plus longuement et termine par le bloc [SCENE].
+        if data.get("type") == "narrator":
+            ollama_payload["options"]["num_predict"] = 260
+        else:
+            ollama_payload["options"].update(personality_sampling(data.get("personality")))
+
+        apply_sampling_overrides(ollama_payload["options"], data.get("scene_preset"))
+
+        if data.get("stream"):
+            ollama_payload["stream"] = True
+            return Response(stream_generation(data, ollama_payload, request_id), mimetype="application/x-ndjson")
+
+        print(f"🚀 [{request_id}] Sending to Ollama...")
 
         response = requests.post(OLLAMA_URL, json=ollama_payload, timeout=45)
 
@@ -211,47 +469,12 @@ def generate():
             result = response.json()
             ai_response = result.get("response", "[Error Ollama response]")
 
-            print(f"✅ Ollama response received (length: {len(ai_response)} characters)")
+            print(f"✅ [{request_id}] Ollama response received (length: {len(ai_response)} characters)")
 
             cleaned_response = clean_response(ai_response)
 
             print(f"🧹 Cleaned response (length: {len(cleaned_response)} characters)")
 
-            character_id = data.get("character_id")
-            persona_id = data.get("persona_id")
-
-           
-            if not data.get("character_img") and character_id:
-                try:
-                    characters_file = os.path.join(os.path.dirname(__file__), "..", "data", "characters.json")
-                    with open(characters_file, "r", encoding="utf-8") as f:
-                        characters = json.load(f)
-                    matching = next((c for c in characters if c["id"] == character_id), None)
-                    if matching:
-                        data["character_img"] = matching.get("img", "")
-                       
-                    else:
-                        print("")
-                except Exception as e:
-                    print(f"{e}")
-
-            if character_id and persona_id and character_id != "unknown" and persona_id != "unknown":
-                try:
-                    
-                    save_to_history(
-                        character_id=character_id,
-                        persona_id=persona_id,
-                        user_message=data.get("user_message", ""),
-                        ai_message=cleaned_response,
-                        character_name=data.get("character_name", "Unknown"),
-                        character_img=data.get("character_img", "")
-                    )
-                    
-                except Exception as save_error:
-                    print(f"❌ Backup error (non-blocking): {save_error}")
-            else:
-                print(f"⚠️ Backup skipped - invalid IDs : char={character_id}, persona={persona_id}")
-
             return jsonify({
                 "response": cleaned_response,
                 "model_used": MODEL_NAME,
@@ -259,26 +482,62 @@ def generate():
                 "status": "success"
             })
         else:
-            print(f"❌ Error Ollama HTTP {response.status_code}: {response.text}")
+            print(f"❌ [{request_id}] Error Ollama HTTP {response.status_code}: {response.text}")
             return jsonify({
                 "response": f"[Error Ollama HTTP {response.status_code}] - {response.text}",
                 "status": "error"
             })
 
     except requests.exceptions.Timeout:
-        print("⏰ Timeout Ollama")
+        print(f"⏰ [{request_id}] Timeout Ollama")
         return jsonify({
             "response": "[Timeout - The model takes too long to respond]",
             "status": "timeout"
         })
     except Exception as e:
-        print(f"❌ Exception: {e}")
+        print(f"❌ [{request_id}] Exception: {e}")
         return jsonify({
             "response": f"[Error Ollama calling : {e}]",
             "status": "error"
         })
 
 
+def stream_generation(data, ollama_payload, request_id):
+    """Relaie les fragments d'Ollama en NDJSON, puis la réponse complète nettoyée (`done`)."""
+    pieces = []
+    print(f"🚀 [{request_id}] Streaming from Ollama...")
+
+    try:
+        with requests.post(OLLAMA_URL, json=ollama_payload, stream=True, timeout=45) as response:
+            if response.status_code != 200:
+                print(f"❌ [{request_id}] Error Ollama HTTP {response.status_code}: {response.text}")
+                yield json.dumps({"error": f"Error Ollama HTTP {response.status_code} - {response.text}"}) + "\n"
+                return
+
+            for line in response.iter_lines():
+                if not line:
+                    continue
+                chunk = json.loads(line)
+                piece = chunk.get("response", "")
+                if piece:
+                    pieces.append(piece)
+                    yield json.dumps({"response": piece}, ensure_ascii=False) + "\n"
+                if chunk.get("done"):
+                    break
+    except requests.exceptions.Timeout:
+        print(f"⏰ [{request_id}] Timeout Ollama")
+        yield json.dumps({"error": "Timeout - The model takes too long to respond"}) + "\n"
+        return
+    except Exception as e:
+        print(f"❌ [{request_id}] Exception: {e}")
+        yield json.dumps({"error": f"Error Ollama calling : {e}"}) + "\n"
+        return
+
+    cleaned_response = clean_response("".join(pieces))
+    print(f"✅ [{request_id}] Ollama stream finished (length: {len(cleaned_response)} characters)")
+    yield json.dumps({"response": cleaned_response, "done": True}, ensure_ascii=False) + "\n"
+
+
 def clean_response(response):
     lines_to_remove = [
         "### Character Roleplay Instructions ###",
@@ -296,114 +555,138 @@ def clean_response(response):
     
     return cleaned.strip()
 
-def save_to_history(character_id, persona_id, user_message, ai_message, character_name="Unknown", character_img=""):
-   
-    base_dir = os.path.join(os.path.dirname(__file__), "..", "data", "history")
-    os.makedirs(base_dir, exist_ok=True)
-    
-    filename = os.path.join(base_dir, f"{character_id}_{persona_id}.json")
-    
-    now = datetime.now().isoformat()
-
-    entry_user = {
-        "role": "user",
-        "content": user_message,
-        "timestamp": now
+@app.route("/next-speaker", methods=["POST"])
+def next_speaker():
+    """Demande au modèle lequel des personnages du groupe doit répondre."""
+    data = request.get_json() or {}
+    request_id = request.headers.get("X-Request-Id", "-")
+    members = data.get("members") or []
+
+    if not members:
+        return jsonify({"speaker_id": None, "status": "error"})
+
+    ollama_payload = {
+        "model": data.get("model") or MODEL_NAME,
+        "prompt": format_next_speaker_prompt(data),
+        "stream": False,
+        "options": {
+            "temperature": 0.2,
+            "num_predict": 12
+        }
     }
 
-    entry_ai = {
-        "role": "assistant",
-        "content": ai_message,
-        "timestamp": now
+    try:
+        response = requests.post(OLLAMA_URL, json=ollama_payload, timeout=20)
+        if response.status_code != 200:
+            print(f"❌ [{request_id}] Error Ollama HTTP {response.status_code}: {response.text}")
+            return jsonify({"speaker_id": None, "status": "error"})
+        answer = response.json().get("response", "")
+    except Exception as e:
+        print(f"❌ [{request_id}] Exception: {e}")
+        return jsonify({"speaker_id": None, "status": "error"})
+
+    speaker_id = match_speaker(answer, members)
+    print(f"🎙️ [{request_id}] Next speaker: {speaker_id} (model said: {answer.strip()!r})")
+    return jsonify({"speaker_id": speaker_id, "status": "success"})
+
+
+def format_next_speaker_prompt(data):
+    members = data.get("members") or []
+    profiles = "\n".join(f"- {m.get('name', 'Unknown')}: {m.get('personality', '')}" for m in members)
+    user_message = data.get("user_message") or GROUP_CONTINUE_MESSAGE
+
+    return f"""You are directing a roleplay scene with several characters.
+
+**CHARACTERS:**
+{profiles}
+
+**CONVERSATION HISTORY:**
+{format_group_memory({"group_id": True, "memory": data.get("memory", [])})}
+
+**LATEST USER MESSAGE:**
+"{user_message}"
+
+Which character should reply next? Pick the one who is addressed, or the one whose personality makes them most likely to react. Answer with the character's name only."""
+
+
+def match_speaker(answer, members):
+    """Premier personnage nommé dans la réponse du modèle."""
+    text = answer.lower()
+    positions = [
+        (text.find(m.get("name", "").lower()), m.get("id"))
+        for m in members
+        if m.get("name") and m.get("name", "").lower() in text
+    ]
+    return min(positions)[1] if positions else None
+
+
+@app.route("/character-draft", methods=["POST"])
+def character_draft():
+    """Rédige un personnage au format JSON ; l'application valide la réponse."""
+    data = request.get_json() or {}
+    request_id = request.headers.get("X-Request-Id", "-")
+
+    ollama_payload = {
+        "model": data.get("model") or MODEL_NAME,
+        "prompt": format_character_draft_prompt(data),
+        "stream": False,
+        "format": "json",
+        "options": {
+            "temperature": 0.8,
+            "top_p": 0.9,
+            "num_ctx": 4096,
+            "num_predict": 900
+        }
     }
 
     try:
-        if os.path.exists(filename):
-            with open(filename, "r", encoding="utf-8") as f:
-                history = json.load(f)
-        else:
-            history = []
-        
-        history.extend([entry_user, entry_ai])
-        
-        if len(history) > 100:
-            history = history[-100:]
-        
-        with open(filename, "w", encoding="utf-8") as f:
-            json.dump(history, f, indent=2, ensure_ascii=False)
-        
-        
-        
-    except Exception as e: 
-        print(f"❌ Error with saving history: {e}")
-        pass
-    
-    
-    try:
-        update_recent_chats_index(character_id, persona_id, character_name, character_img)
+        response = requests.post(OLLAMA_URL, json=ollama_payload, timeout=90)
+        if response.status_code != 200:
+            print(f"❌ [{request_id}] Error Ollama HTTP {response.status_code}: {response.text}")
+            return jsonify({"response": f"[Error Ollama HTTP {response.status_code}] - {response.text}", "status": "error"})
+        answer = response.json().get("response", "")
+    except requests.exceptions.Timeout:
+        print(f"⏰ [{request_id}] Timeout Ollama")
+        return jsonify({"response": "[Timeout - The model takes too long to respond]", "status": "timeout"})
     except Exception as e:
-        print(f"❌ Error updating recent chats index: {e}")
+        print(f"❌ [{request_id}] Exception: {e}")
+        return jsonify({"response": f"[Error Ollama calling : {e}]", "status": "error"})
 
-def update_recent_chats_index(character_id, persona_id, character_name, character_img):
-    index_file = os.path.join(os.path.dirname(__file__), "..", "data", "recent_chats.json")
-    
-    try:
-        
-        if os.path.exists(index_file):
-            with open(index_file, "r", encoding="utf-8") as f:
-                recent_chats = json.load(f)
-        else:
-            recent_chats = []
+    print(f"🧙 [{request_id}] Character draft received (length: {len(answer)} characters)")
+    return jsonify({"response": answer, "status": "success"})
 
-        
-        existing_chat = None
-        for i, chat in enumerate(recent_chats):
-            if chat.get("characterId") == character_id and chat.get("personaId") == persona_id:
-                existing_chat = i
-                break
 
-       
-        
-        if character_img and character_img.strip():
-            if character_img.startswith("/assets/"):
-                candidate = character_img
-            else:
-                candidate = f"/assets/characters/{character_img}"
-
-           
-            frontend_public_dir = os.path.abspath(os.path.join(os.path.dirname(__file__), "..", "..", "tauri-ui", "public"))
-            absolute_path = os.path.join(frontend_public_dir, candidate.lstrip("/"))
-            if os.path.exists(absolute_path):
-                final_img = candidate
-            else:
-                final_img = "/assets/characters/default.png"
-        else:
-            final_img = "/assets/characters/default.png"
-
-        chat_entry = {
-            "characterId": character_id,
-            "personaId": persona_id,
-            "name": character_name,
-            "img": final_img,
-            "lastUsed": int(datetime.now().timestamp())
-        }
+def format_character_draft_prompt(data):
+    tags = ", ".join(data.get("tags") or []) or "none, choose two to five fitting tags"
+    retry = ""
+    if data.get("previous_error"):
+        retry = f"""
+Your previous answer was rejected: {data["previous_error"]}. Fix this and follow the format exactly.
+"""
 
-        if existing_chat is not None:
-            recent_chats[existing_chat] = chat_entry
-        else:
-            recent_chats.append(chat_entry)
+    return f"""You are a character designer for an immersive roleplay application.
 
-       
-        recent_chats.sort(key=lambda x: x.get("lastUsed", 0), reverse=True)
-        recent_chats = recent_chats[:10]
+Create one original character from this concept:
+"{data.get("concept", "")}"
 
-        with open(index_file, "w", encoding="utf-8") as f:
-            json.dump(recent_chats, f, indent=2, ensure_ascii=False)
+Tag hints: {tags}
+{retry}
+Answer with a single JSON object and nothing else, using exactly these keys:
+{{
+  "name": "the character's full name",
+  "tagline": "one short sentence summing up their personality",
+  "description": "two or three sentences about their appearance and manner",
+  "greeting": "the first message they send, in character, with actions in *italics*",
+  "definition": "a detailed paragraph about their background, motivations, speech patterns and quirks",
+  "tags": ["lowercase", "genre", "tags"],
+  "alternate_greetings": ["one or two other possible first messages"],
+  "example_dialogues": [{{"user": "a message from the user", "character": "the character's reply, in their voice"}}],
+  "personality": {{"warmth": 0-100, "verbosity": 0-100, "formality": 0-100, "humor": 0-100, "aggression": 0-100, "memory_depth": 1-30}}
+}}
 
-        
+Every text field must be filled. Sliders are integers; 50 is neutral.
 
-    except Exception as e:
-        print(f"❌ Erreur mise à jour index chats récents: {e}")
+{format_language_instruction(data.get("language"))}"""
 
 
 @app.route("/models", methods=["GET"])
@@ -418,7 +701,8 @@ def health_check():
     return jsonify({
         "status": "healthy", 
         "service": "PersonAI LLM Service",
-        "model": MODEL_NAME
+        "model": MODEL_NAME,
+        "version": SERVICE_VERSION
     })
 
 @app.route("/history", methods=["GET"])
@@ -465,4 +749,4 @@ if __name__ == "__main__":
     print(f"📍 Model used: {MODEL_NAME}")
     print("🔗 Ollama URL:", OLLAMA_URL)
 
-    app.run(host="0.0.0.0", port=5050, debug=True)
+    app.run(host="0.0.0.0", port=PORT, debug=True)
@@ -0,0 +1,124 @@
+use crate::error::{AppError, AppResult};
+use crate::i18n::{t, tf, Msg};
+use crate::file_utils::{read_characters_file, read_personas_file, sha256_hex, ASSET_STORE_PATH, ASSET_STORE_URL};
+use crate::types::StoredAsset;
+use crate::vault::Vault;
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader, Limits};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+pub const THUMBNAIL_SIZES: [u32; 3] = [64, 128, 256];
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// Valide et normalise une image puis la range dans le magasin sous `<sha256>.png`,
+/// accompagnée de ses miniatures carrées `<sha256>_<taille>.png`.
+/// L'identifiant renvoyé est le chemin public à enregistrer dans `img`.
+pub fn store_image(data: &[u8]) -> AppResult<StoredAsset> {
+    if data.is_empty() {
+        return Err(AppError::invalid_field("data", t(Msg::EmptyImage)));
+    }
+    if data.len() > MAX_IMAGE_BYTES {
+        return Err(AppError::invalid_field(
+            "data",
+            tf(Msg::ImageTooLarge, &[&(MAX_IMAGE_BYTES / (1024 * 1024))]),
+        ));
+    }
+
+    let format = image::guess_format(data).map_err(|_| AppError::invalid_field("data", t(Msg::UnknownImageFormat)))?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP) {
+        return Err(AppError::invalid_field("data", tf(Msg::UnsupportedImageFormat, &[&format!("{:?}", format)])));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let img = reader.decode().map_err(|e| AppError::invalid_field("data", tf(Msg::InvalidImage, &[&e])))?;
+
+    // Le ré-encodage à partir des pixels élimine EXIF, profils et autres métadonnées.
+    let png = encode_png(&img)?;
+    let hash = sha256_hex(&png);
+
+    let store = Path::new(ASSET_STORE_PATH);
+    fs::create_dir_all(store).map_err(|e| AppError::create_dir(store, e))?;
+
+    let original = store.join(format!("{}.png", hash));
+    if !original.exists() {
+        fs::write(&original, &png).map_err(|e| AppError::write(&original, e))?;
+    }
+
+    let mut thumbnails = Vec::with_capacity(THUMBNAIL_SIZES.len());
+    for size in THUMBNAIL_SIZES {
+        let file_name = format!("{}_{}.png", hash, size);
+        let dest = store.join(&file_name);
+        if !dest.exists() {
+            let thumbnail = encode_png(&img.resize_to_fill(size, size, FilterType::Lanczos3))?;
+            fs::write(&dest, thumbnail).map_err(|e| AppError::write(&dest, e))?;
+        }
+        thumbnails.push(format!("{}/{}", ASSET_STORE_URL, file_name));
+    }
+
+    Ok(StoredAsset {
+        id: format!("{}/{}.png", ASSET_STORE_URL, hash),
+        hash,
+        width: img.width(),
+        height: img.height(),
+        thumbnails,
+    })
+}
+
+/// Supprime du magasin les images (et leurs miniatures) qu'aucun personnage ni
+/// aucune persona ne référence. Renvoie le nombre de fichiers supprimés.
+pub fn collect_garbage(vault: &Vault) -> AppResult<usize> {
+    let mut referenced = HashSet::new();
+
+    let images = read_characters_file()?
+        .into_iter()
+        .map(|c| c.img)
+        .chain(read_personas_file(vault)?.into_iter().map(|p| p.img));
+
+    for img in images {
+        if let Some(hash) = stored_hash(&img) {
+            referenced.insert(hash.to_string());
+        }
+    }
+
+    let mut removed = 0;
+    if let Ok(entries) = fs::read_dir(ASSET_STORE_PATH) {
+        for entry in entries.flatten() {
+            let Ok(file_name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let hash = file_name
+                .trim_end_matches(".png")
+                .split('_')
+                .next()
+                .unwrap_or_default();
+
+            if !referenced.contains(hash) && fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+fn stored_hash(img: &str) -> Option<&str> {
+    img.strip_prefix(ASSET_STORE_URL)?
+        .strip_prefix('/')?
+        .strip_suffix(".png")
+}
+
+fn encode_png(img: &image::DynamicImage) -> AppResult<Vec<u8>> {
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| AppError::parse("image PNG", e))?;
+    Ok(png)
+}
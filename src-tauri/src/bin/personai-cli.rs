@@ -0,0 +1,498 @@
+//! Client en ligne de commande : gère la bibliothèque et discute avec les personnages sans
+//! ouvrir la fenêtre. Il lit les mêmes fichiers `data/` que l'application et lance les
+//! services de la même façon.
+
+use app_lib::chat;
+use app_lib::error::{AppError, AppResult, Entity};
+use app_lib::file_utils;
+use app_lib::i18n::{t, Msg};
+use app_lib::openai;
+use app_lib::personality;
+use app_lib::transcript;
+use app_lib::wizard;
+use app_lib::types::{AppState, Character, HealthState, Persona, TranscriptFormat};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Variable lue avant de demander la phrase secrète du coffre.
+const PASSPHRASE_ENV: &str = "PERSONAI_VAULT_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(name = "personai-cli", version, about = "PersonAi en ligne de commande")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Gérer les personnages
+    #[command(subcommand)]
+    Characters(CharacterCommand),
+    /// Gérer les personas
+    #[command(subcommand)]
+    Personas(PersonaCommand),
+    /// Discuter avec un personnage (`/reset` efface la conversation, `/quit` quitte) ; sans
+    /// persona, celle par défaut du personnage
+    Chat { character_id: String, persona_id: Option<String> },
+    /// Consulter et exporter les historiques
+    #[command(subcommand)]
+    History(HistoryCommand),
+    /// Vérifier l'état des services
+    Health,
+    /// Lancer le serveur compatible OpenAI, même s'il est désactivé dans les réglages
+    Serve,
+}
+
+#[derive(Subcommand)]
+enum CharacterCommand {
+    List,
+    Show { id: String },
+    /// Lister les préréglages de personnalité
+    Presets,
+    /// Faire rédiger un personnage par le modèle à partir d'une courte idée
+    Draft {
+        concept: String,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Enregistrer le brouillon au lieu de seulement l'afficher
+        #[arg(long)]
+        save: bool,
+    },
+    Create {
+        #[arg(long)]
+        name: String,
+        #[command(flatten)]
+        details: CharacterDetails,
+    },
+    Edit {
+        id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        details: CharacterDetails,
+    },
+    Delete { id: String },
+}
+
+#[derive(Args)]
+struct CharacterDetails {
+    #[arg(long)]
+    tagline: Option<String>,
+    #[arg(long)]
+    description: Option<String>,
+    #[arg(long)]
+    greeting: Option<String>,
+    #[arg(long)]
+    definition: Option<String>,
+    #[arg(long)]
+    img: Option<String>,
+    /// Préréglage de personnalité (voir `characters presets`)
+    #[arg(long)]
+    preset: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum PersonaCommand {
+    List,
+    Show { id: String },
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        background: String,
+        #[arg(long, default_value = "")]
+        img: String,
+    },
+    Edit {
+        id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        background: Option<String>,
+        #[arg(long)]
+        img: Option<String>,
+    },
+    Delete { id: String },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    List,
+    Show { character_id: String, persona_id: String },
+    Export {
+        character_id: String,
+        persona_id: String,
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    Delete { character_id: String, persona_id: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Markdown,
+    Html,
+    Text,
+    Jsonl,
+}
+
+impl From<Format> for TranscriptFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Markdown => TranscriptFormat::Markdown,
+            Format::Html => TranscriptFormat::Html,
+            Format::Text => TranscriptFormat::Text,
+            Format::Jsonl => TranscriptFormat::Jsonl,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let state = AppState::new();
+
+    let result = match cli.command {
+        Command::Characters(command) => characters(&state, command),
+        Command::Personas(command) => personas(&state, command),
+        Command::Chat { character_id, persona_id } => chat_loop(&state, &character_id, persona_id),
+        Command::History(command) => history(&state, command),
+        Command::Health => health(&state),
+        Command::Serve => serve(&state),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn characters(state: &AppState, command: CharacterCommand) -> AppResult<()> {
+    match command {
+        CharacterCommand::List => {
+            for c in file_utils::read_characters_file()? {
+                println!("{:<32} {:<24} {}", c.id, c.name, c.tagline);
+            }
+        }
+        CharacterCommand::Show { id } => {
+            let character = find_character(&id)?;
+            println!("{}", serde_json::to_string_pretty(&character).map_err(|e| AppError::parse(&id, e))?);
+        }
+        CharacterCommand::Presets => {
+            for p in personality::builtin_presets() {
+                let v = p.personality;
+                println!(
+                    "{:<12} {:<24} warmth {:>3} · verbosity {:>3} · formality {:>3} · humor {:>3} · aggression {:>3} · memory {:>2}",
+                    p.id, p.name, v.warmth, v.verbosity, v.formality, v.humor, v.aggression, v.memory_depth
+                );
+            }
+        }
+        CharacterCommand::Draft { concept, tags, save } => {
+            println!("⏳ Démarrage des services…");
+            if let Err(errors) = state.supervisor.start_all() {
+                for e in errors {
+                    eprintln!("❌ {} — {}", e, e.hint());
+                }
+            }
+            let draft = tauri::async_runtime::block_on(wizard::generate_character_draft(state, &concept, &tags));
+            state.supervisor.stop_all();
+
+            let character = draft?;
+            println!("{}", serde_json::to_string_pretty(&character).map_err(|e| AppError::parse(&character.id, e))?);
+            if save {
+                let id = character.id.clone();
+                file_utils::update_character(character)?;
+                println!("✅ {}", id);
+            }
+        }
+        CharacterCommand::Create { name, details } => {
+            let mut character = Character {
+                id: new_id(&name),
+                name,
+                tagline: String::new(),
+                description: String::new(),
+                greeting: String::new(),
+                definition: String::new(),
+                tags: HashMap::new(),
+                img: String::new(),
+                alternate_greetings: vec![],
+                example_dialogues: vec![],
+                personality: None,
+                default_persona_id: None,
+            };
+            apply_details(&mut character, details)?;
+            let id = character.id.clone();
+            file_utils::update_character(character)?;
+            println!("✅ {}", id);
+        }
+        CharacterCommand::Edit { id, name, details } => {
+            let mut character = find_character(&id)?;
+            if let Some(name) = name {
+                character.name = name;
+            }
+            apply_details(&mut character, details)?;
+            file_utils::update_character(character)?;
+            println!("✅ {}", id);
+        }
+        CharacterCommand::Delete { id } => {
+            find_character(&id)?;
+            file_utils::delete_character_from_file(&id)?;
+            println!("🗑️ {}", id);
+        }
+    }
+    Ok(())
+}
+
+fn apply_details(character: &mut Character, details: CharacterDetails) -> AppResult<()> {
+    let fields = [
+        (&mut character.tagline, details.tagline),
+        (&mut character.description, details.description),
+        (&mut character.greeting, details.greeting),
+        (&mut character.definition, details.definition),
+        (&mut character.img, details.img),
+    ];
+    for (target, value) in fields {
+        if let Some(value) = value {
+            *target = value;
+        }
+    }
+    if let Some(preset) = details.preset {
+        character.personality = Some(personality::find_preset(&preset)?.personality);
+    }
+    Ok(())
+}
+
+fn find_character(id: &str) -> AppResult<Character> {
+    file_utils::read_characters_file()?
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| AppError::not_found(Entity::Character, id))
+}
+
+fn personas(state: &AppState, command: PersonaCommand) -> AppResult<()> {
+    unlock_vault(state)?;
+
+    match command {
+        PersonaCommand::List => {
+            for p in file_utils::read_personas_file(&state.vault)? {
+                println!("{:<32} {}", p.id, p.display_name);
+            }
+        }
+        PersonaCommand::Show { id } => {
+            let persona = find_persona(state, &id)?;
+            println!("{}", serde_json::to_string_pretty(&persona).map_err(|e| AppError::parse(&id, e))?);
+        }
+        PersonaCommand::Create { name, background, img } => {
+            let persona = Persona {
+                id: new_id(&name),
+                display_name: name,
+                background,
+                img,
+            };
+            let id = persona.id.clone();
+            file_utils::update_persona(&state.vault, persona)?;
+            println!("✅ {}", id);
+        }
+        PersonaCommand::Edit { id, name, background, img } => {
+            let mut persona = find_persona(state, &id)?;
+            if let Some(name) = name {
+                persona.display_name = name;
+            }
+            if let Some(background) = background {
+                persona.background = background;
+            }
+            if let Some(img) = img {
+                persona.img = img;
+            }
+            file_utils::update_persona(&state.vault, persona)?;
+            println!("✅ {}", id);
+        }
+        PersonaCommand::Delete { id } => {
+            find_persona(state, &id)?;
+            file_utils::delete_persona_from_file(&state.vault, &id)?;
+            println!("🗑️ {}", id);
+        }
+    }
+    Ok(())
+}
+
+fn find_persona(state: &AppState, id: &str) -> AppResult<Persona> {
+    file_utils::read_personas_file(&state.vault)?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AppError::not_found(Entity::Persona, id))
+}
+
+fn chat_loop(state: &AppState, character_id: &str, persona_id: Option<String>) -> AppResult<()> {
+    unlock_vault(state)?;
+    let character = find_character(character_id)?;
+    let persona_id = persona_id
+        .or_else(|| character.default_persona_id.clone())
+        .ok_or_else(|| AppError::invalid_field("persona_id", t(Msg::NoDefaultPersona)))?;
+    find_persona(state, &persona_id)?;
+
+    println!("⏳ Démarrage des services…");
+    if let Err(errors) = state.supervisor.start_all() {
+        for e in errors {
+            eprintln!("❌ {} — {}", e, e.hint());
+        }
+    }
+
+    let result = run_chat(state, &character, &persona_id);
+    state.supervisor.stop_all();
+    result
+}
+
+fn run_chat(state: &AppState, character: &Character, persona_id: &str) -> AppResult<()> {
+    let fresh = file_utils::read_chat_history(&state.vault, &character.id, persona_id)?.is_empty();
+    let mut history = chat::start_session(&state.vault, character, persona_id, None)?;
+    if let (true, Some(greeting)) = (fresh, history.first()) {
+        println!("{} : {}\n", character.name, greeting.content);
+    }
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().map_err(|e| AppError::write("stdout", e))?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(|e| AppError::read("stdin", e))? == 0 {
+            return Ok(());
+        }
+        let input = line.trim();
+
+        match input {
+            "" => continue,
+            "/quit" | "/exit" => return Ok(()),
+            "/reset" => {
+                reset(state, &character.id, persona_id)?;
+                history = chat::start_session(&state.vault, character, persona_id, None)?;
+                println!("🔄 Conversation réinitialisée\n");
+                if let Some(greeting) = history.first() {
+                    println!("{} : {}\n", character.name, greeting.content);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        print!("{} : ", character.name);
+        let reply = tauri::async_runtime::block_on(chat::stream_message(
+            state,
+            input,
+            &character.id,
+            persona_id,
+            history.clone(),
+            |token| {
+                print!("{}", token);
+                let _ = io::stdout().flush();
+            },
+        ));
+        println!("\n");
+
+        match reply {
+            Ok(_) => history = file_utils::read_chat_history(&state.vault, &character.id, persona_id)?,
+            Err(e) => eprintln!("❌ {}", e),
+        }
+    }
+}
+
+fn reset(state: &AppState, character_id: &str, persona_id: &str) -> AppResult<()> {
+    file_utils::delete_chat_history(&state.vault, character_id, persona_id)
+}
+
+fn history(state: &AppState, command: HistoryCommand) -> AppResult<()> {
+    unlock_vault(state)?;
+
+    match command {
+        HistoryCommand::List => {
+            for (character_id, persona_id) in file_utils::list_chat_histories() {
+                let count = file_utils::read_chat_history(&state.vault, &character_id, &persona_id)
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+                println!("{:<32} {:<32} {} messages", character_id, persona_id, count);
+            }
+        }
+        HistoryCommand::Show { character_id, persona_id } => {
+            for message in file_utils::read_chat_history(&state.vault, &character_id, &persona_id)? {
+                let when = message.timestamp.unwrap_or_default();
+                println!("[{}] {} : {}", when, message.role, message.content);
+            }
+        }
+        HistoryCommand::Export { character_id, persona_id, format, out } => {
+            transcript::export_transcript(&state.vault, &character_id, &persona_id, format.into(), &out)?;
+            println!("✅ {}", out.display());
+        }
+        HistoryCommand::Delete { character_id, persona_id } => {
+            file_utils::delete_chat_history(&state.vault, &character_id, &persona_id)?;
+            println!("🗑️ {}_{}", character_id, persona_id);
+        }
+    }
+    Ok(())
+}
+
+fn health(state: &AppState) -> AppResult<()> {
+    let report = state.health.refresh();
+
+    for service in &report.services {
+        let icon = match service.state {
+            HealthState::Healthy => "✅",
+            HealthState::Unhealthy => "⚠️",
+            HealthState::Unreachable => "❌",
+        };
+        let latency = service.latency_ms.map(|ms| format!("{} ms", ms)).unwrap_or_default();
+        let detail = service
+            .model
+            .clone()
+            .or_else(|| service.last_error.clone())
+            .unwrap_or_default();
+        println!("{} {:<12} {:>8}  {}", icon, service.service.label(), latency, detail);
+    }
+
+    if report.healthy {
+        Ok(())
+    } else {
+        Err(AppError::backend("PersonAi", None, t(Msg::ServicesUnhealthy)))
+    }
+}
+
+fn serve(state: &AppState) -> AppResult<()> {
+    unlock_vault(state)?;
+    if let Err(errors) = state.supervisor.start_all() {
+        for e in errors {
+            eprintln!("❌ {} — {}", e, e.hint());
+        }
+    }
+
+    let result = tauri::async_runtime::block_on(openai::serve(state.clone()));
+    state.supervisor.stop_all();
+    result
+}
+
+/// Les personas et les historiques sont chiffrés quand le coffre est activé.
+fn unlock_vault(state: &AppState) -> AppResult<()> {
+    if !state.vault.is_enabled() || state.vault.is_unlocked() {
+        return Ok(());
+    }
+
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        // Saisie sans écho sur le terminal ; un script passe plutôt par PASSPHRASE_ENV.
+        Err(_) => rpassword::prompt_password(t(Msg::PassphrasePrompt)).map_err(|e| AppError::read("stdin", e))?,
+    };
+    state.vault.unlock(&passphrase)
+}
+
+/// Même forme d'identifiant que l'interface : `nom-en-minuscules_horodatage`.
+fn new_id(name: &str) -> String {
+    let slug = name.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase();
+    format!("{}_{}", slug, chrono::Local::now().timestamp_millis())
+}
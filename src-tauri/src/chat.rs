@@ -0,0 +1,294 @@
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils;
+use crate::http::Retry;
+use crate::i18n::{t, tf, Msg};
+use crate::presets;
+use crate::relationship;
+use crate::scene;
+use crate::types::{
+    AppState, AskRequestForChat, AskResponse, Character, ChatMessage, ChatSession, ExampleDialogue, ServiceKind,
+    StreamChunk,
+};
+use crate::vault::Vault;
+use rand::Rng;
+
+/// Nom donné à l'utilisateur sans persona ; les consignes du modèle sont en anglais.
+const UNNAMED_USER: &str = "the user";
+
+/// Envoie un message au personnage via l'API Go et renvoie sa réponse.
+pub async fn send_message(
+    state: &AppState,
+    input: &str,
+    character_id: &str,
+    persona_id: &str,
+    history: Vec<ChatMessage>,
+) -> AppResult<String> {
+    let request = ask_request(state, input, character_id, persona_id, history, false)?;
+
+    let answer = state
+        .http
+        .post_json::<AskRequestForChat, AskResponse>(ServiceKind::Go, &ask_url(state), &request, Retry::ConnectOnly)
+        .await?
+        .answer;
+
+    record_exchange(state, input, character_id, persona_id, &answer)?;
+    Ok(answer)
+}
+
+/// Comme `send_message`, mais chaque fragment est passé à `on_token` dès sa génération.
+/// La réponse renvoyée est la version finale, nettoyée par le service Python.
+pub async fn stream_message(
+    state: &AppState,
+    input: &str,
+    character_id: &str,
+    persona_id: &str,
+    history: Vec<ChatMessage>,
+    mut on_token: impl FnMut(&str),
+) -> AppResult<String> {
+    let request = ask_request(state, input, character_id, persona_id, history, true)?;
+
+    let mut answer = None;
+    let mut failure = None;
+    state
+        .http
+        .post_lines(ServiceKind::Go, &ask_url(state), &request, Retry::ConnectOnly, |line| {
+            match serde_json::from_str::<StreamChunk>(line) {
+                Ok(StreamChunk { error: Some(e), .. }) => failure = Some(e),
+                Ok(chunk) if chunk.done => answer = Some(chunk.response),
+                Ok(chunk) => on_token(&chunk.response),
+                Err(e) => failure = Some(e.to_string()),
+            }
+        })
+        .await?;
+
+    if let Some(reason) = failure {
+        return Err(AppError::backend(ServiceKind::Python.label(), None, reason));
+    }
+    let answer = answer.ok_or_else(|| AppError::backend(ServiceKind::Go.label(), None, t(Msg::StreamInterrupted)))?;
+
+    record_exchange(state, input, character_id, persona_id, &answer)?;
+    Ok(answer)
+}
+
+/// Ouvre la conversation : un historique vide reçoit comme premier message la salutation
+/// `greeting` (0 pour `greeting`, puis les `alternate_greetings`) ou, à défaut, une
+/// salutation tirée au hasard. Renvoie l'historique.
+pub fn start_session(
+    vault: &Vault,
+    character: &Character,
+    persona_id: &str,
+    greeting: Option<usize>,
+) -> AppResult<Vec<ChatMessage>> {
+    let persona = file_utils::read_personas_file(vault)?
+        .into_iter()
+        .find(|p| p.id == persona_id)
+        .ok_or_else(|| AppError::not_found(Entity::Persona, persona_id))?;
+
+    let history = file_utils::read_chat_history(vault, &character.id, persona_id)?;
+    if !history.is_empty() {
+        return Ok(history);
+    }
+
+    let greetings: Vec<&str> = std::iter::once(&character.greeting)
+        .chain(&character.alternate_greetings)
+        .map(|g| g.trim())
+        .filter(|g| !g.is_empty())
+        .collect();
+    let chosen = match greeting {
+        Some(i) => *greetings
+            .get(i)
+            .ok_or_else(|| AppError::invalid_field("greeting", tf(Msg::GreetingOutOfRange, &[&i])))?,
+        None if greetings.is_empty() => return Ok(history),
+        None => greetings[rand::thread_rng().gen_range(0..greetings.len())],
+    };
+
+    let message = ChatMessage {
+        role: "assistant".to_string(),
+        content: expand_macros(chosen, &character.name, &persona.display_name),
+        timestamp: Some(chrono::Local::now().to_rfc3339()),
+        speaker_id: None,
+    };
+    file_utils::append_chat_messages(vault, &character.id, persona_id, vec![message.clone()])?;
+    Ok(vec![message])
+}
+
+fn ask_url(state: &AppState) -> String {
+    format!("{}/ask", state.supervisor.base_url(ServiceKind::Go))
+}
+
+pub(crate) fn ask_request(
+    state: &AppState,
+    input: &str,
+    character_id: &str,
+    persona_id: &str,
+    history: Vec<ChatMessage>,
+    stream: bool,
+) -> AppResult<AskRequestForChat> {
+    let status = state.supervisor.system_status();
+    if !status.chat_available {
+        let (service, reason) = status
+            .issues
+            .first()
+            .map(|i| (i.service.label(), format!(" : {} ({})", i.message, i.hint)))
+            .unwrap_or(("Chat", String::new()));
+        return Err(AppError::backend(service, None, tf(Msg::ChatUnavailable, &[&reason])));
+    }
+
+    let settings = state.settings.get();
+    let persona = file_utils::read_personas_file(&state.vault)?.into_iter().find(|p| p.id == persona_id);
+    let scene = scene::read_scene(&state.vault, &ChatSession::Direct {
+        character_id: character_id.to_string(),
+        persona_id: persona_id.to_string(),
+    })?;
+
+    Ok(AskRequestForChat {
+        question: input.to_string(),
+        character_id: character_id.to_string(),
+        user_id: persona_id.to_string(),
+        model: settings.model,
+        memory: history,
+        language: settings.locale,
+        stream,
+        group_id: None,
+        members: vec![],
+        scene_preset: presets::preset_for(scene.as_ref()),
+        scene,
+        relationship: relationship::read_relationship(&state.vault, character_id, persona_id)?,
+        character: file_utils::read_characters_file()?
+            .into_iter()
+            .find(|c| c.id == character_id)
+            .map(|c| expand_character(&c, persona.as_ref().map_or(UNNAMED_USER, |p| p.display_name.as_str()))),
+        persona,
+    })
+}
+
+/// Copie du personnage dont les champs lus par le modèle ont leurs macros développées.
+/// L'application est seule à les développer : les services reçoivent le résultat.
+pub fn expand_character(character: &Character, user_name: &str) -> Character {
+    let expand = |text: &str| expand_macros(text, &character.name, user_name);
+    Character {
+        tagline: expand(&character.tagline),
+        description: expand(&character.description),
+        greeting: expand(&character.greeting),
+        definition: expand(&character.definition),
+        alternate_greetings: character.alternate_greetings.iter().map(|g| expand(g)).collect(),
+        example_dialogues: character
+            .example_dialogues
+            .iter()
+            .map(|d| ExampleDialogue { user: expand(&d.user), character: expand(&d.character) })
+            .collect(),
+        ..character.clone()
+    }
+}
+
+/// Remplace `{{char}}` et `{{user}}` (sans tenir compte de la casse ni des espaces
+/// intérieurs) par les noms du personnage et de la persona. Les noms insérés ne sont pas
+/// développés à leur tour.
+pub fn expand_macros(text: &str, char_name: &str, user_name: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = &rest[start + 2..start + len];
+        let value = if name.trim().eq_ignore_ascii_case("char") {
+            Some(char_name)
+        } else if name.trim().eq_ignore_ascii_case("user") {
+            Some(user_name)
+        } else {
+            None
+        };
+
+        out.push_str(&rest[..start]);
+        match value {
+            Some(value) => {
+                out.push_str(value);
+                rest = &rest[start + len + 2..];
+            }
+            // Seules les accolades sont recopiées : `{{ {{char}}` développe encore `{{char}}`.
+            None => {
+                out.push_str("{{");
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Met à jour l'état de la relation puis ajoute l'échange à l'historique. L'application en
+/// est la seule rédactrice : les services ne font que lire celui qu'on leur envoie.
+fn record_exchange(state: &AppState, input: &str, character_id: &str, persona_id: &str, answer: &str) -> AppResult<()> {
+    if let Err(e) = relationship::record_turn(&state.vault, character_id, persona_id, input, answer) {
+        eprintln!("⚠️ État de la relation non mis à jour: {}", e);
+    }
+
+    let now = chrono::Local::now().to_rfc3339();
+    file_utils::append_chat_messages(&state.vault, character_id, persona_id, vec![
+        ChatMessage { role: "user".to_string(), content: input.to_string(), timestamp: Some(now.clone()), speaker_id: None },
+        ChatMessage { role: "assistant".to_string(), content: answer.to_string(), timestamp: Some(now), speaker_id: None },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn expands_known_macros_case_insensitively() {
+        assert_eq!(
+            expand_macros("{{char}} salue {{ User }}, puis {{CHAR}} sourit.", "Alice", "Bob"),
+            "Alice salue Bob, puis Alice sourit."
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_and_unterminated_macros_untouched() {
+        assert_eq!(expand_macros("{{time}} et {{char}}", "Alice", "Bob"), "{{time}} et Alice");
+        assert_eq!(expand_macros("{{user}} dit {{char", "Alice", "Bob"), "Bob dit {{char");
+    }
+
+    #[test]
+    fn substituted_names_are_not_expanded_again() {
+        assert_eq!(expand_macros("{{char}}", "{{user}}", "Bob"), "{{user}}");
+    }
+
+    #[test]
+    fn stray_braces_do_not_hide_a_following_macro() {
+        assert_eq!(expand_macros("{{ {{char}}", "Alice", "Bob"), "{{ Alice");
+        assert_eq!(expand_macros("{{}} {{user}}", "Alice", "Bob"), "{{}} Bob");
+    }
+
+    #[test]
+    fn every_field_sent_to_the_model_is_expanded() {
+        let character = Character {
+            id: "alice_1".to_string(),
+            name: "Alice".to_string(),
+            tagline: "L'amie de {{user}}".to_string(),
+            description: "{{char}} tient une librairie.".to_string(),
+            greeting: "Bonjour {{user}} !".to_string(),
+            definition: "{{char}} tutoie {{user}}.".to_string(),
+            tags: HashMap::new(),
+            img: "placeholder.png".to_string(),
+            alternate_greetings: vec!["Te revoilà, {{user}}.".to_string()],
+            example_dialogues: vec![ExampleDialogue {
+                user: "Salut {{char}}".to_string(),
+                character: "Salut {{user}}".to_string(),
+            }],
+            personality: None,
+            default_persona_id: None,
+        };
+        let expanded = expand_character(&character, "Bob");
+
+        assert_eq!(expanded.tagline, "L'amie de Bob");
+        assert_eq!(expanded.description, "Alice tient une librairie.");
+        assert_eq!(expanded.greeting, "Bonjour Bob !");
+        assert_eq!(expanded.definition, "Alice tutoie Bob.");
+        assert_eq!(expanded.alternate_greetings, vec!["Te revoilà, Bob."]);
+        assert_eq!(expanded.example_dialogues[0].user, "Salut Alice");
+        assert_eq!(expanded.example_dialogues[0].character, "Salut Bob");
+        assert_eq!(expanded.id, "alice_1");
+    }
+}
@@ -1,197 +1,411 @@
-use crate::file_utils::{self, copy_image_file, delete_character_from_file, read_characters_file};
-use crate::services::{
-    self, check_service_health, make_http_request, make_simple_post_request, start_go_service, start_python_service, SERVICE_STARTUP_DELAY
+use crate::assets;
+use crate::chat;
+use crate::error::{AppError, AppResult, Entity};
+use crate::i18n::{t, Msg};
+use crate::file_utils::{self, delete_character_from_file, read_characters_file};
+use crate::group;
+use crate::personality;
+use crate::presets;
+use crate::relationship;
+use crate::scene;
+use crate::library;
+use crate::snapshots;
+use crate::transcript;
+use crate::wizard;
+use crate::http::Retry;
+use crate::types::{
+    AppState, AskRequest, AskResponse, Character, CharacterPreset, ChatMessage, ChatSession, GroupChat, HealthReport, ImportOptions, ImportReport,
+    LibraryManifest, LogLevel, LogLine, Narration, Persona, RelationshipState, ScenePreset, SceneState, Settings, RecentChat, ResetRequest, ServiceHealth, ServiceKind, ServiceStatus, SnapshotDiff, SnapshotInfo,
+    StoredAsset, SystemStatus, TranscriptFormat, VaultStatus,
 };
-use crate::types::{AppState, AskRequest, AskRequestForChat, AskResponse, Character, ChatMessage, Persona, RecentChat, ResetRequest};
-use std::sync::atomic::Ordering;
-use std::thread;
-use std::time::Duration;
-use tauri::State;
-use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
 
 
 #[tauri::command]
-pub async fn start_services(state: State<'_, AppState>) -> Result<String, String> {
-    if state.services_running.load(Ordering::Relaxed) {
-        return Ok("Services déjà démarrés".to_string());
+pub async fn start_services(state: State<'_, AppState>) -> AppResult<String> {
+    let supervisor = Arc::clone(&state.supervisor);
+    let started = tauri::async_runtime::spawn_blocking(move || supervisor.start_all())
+        .await?
+        .map_err(|mut errors| {
+            // Le premier échec garde son code ; les suivants n'en sont souvent que la conséquence.
+            let first = errors.remove(0);
+            match AppError::from(first) {
+                AppError::Backend { service, status, reason } if !errors.is_empty() => {
+                    let others = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n");
+                    AppError::backend(service, status, format!("{}\n{}", reason, others))
+                }
+                error => error,
+            }
+        })?;
+
+    if !started {
+        return Ok(t(Msg::ServicesAlreadyStarted).to_string());
     }
 
-    start_python_service();
-    thread::sleep(Duration::from_secs(SERVICE_STARTUP_DELAY));
+    Ok(t(Msg::ServicesStarted).to_string())
+}
 
-    start_go_service();
-    thread::sleep(Duration::from_secs(SERVICE_STARTUP_DELAY));
+#[tauri::command]
+pub fn start_service(state: State<'_, AppState>, service: ServiceKind) -> AppResult<()> {
+    Ok(state.supervisor.start_in_background(service)?)
+}
 
-    state.services_running.store(true, Ordering::Relaxed);
-    Ok("Services démarrés avec succès".to_string())
+#[tauri::command]
+pub fn stop_service(state: State<'_, AppState>, service: ServiceKind) {
+    state.supervisor.stop(service)
 }
 
 #[tauri::command]
-pub async fn ask_question(request: AskRequest) -> Result<AskResponse, String> {
-    make_http_request("/ask", &request).await
+pub fn restart_service(state: State<'_, AppState>, service: ServiceKind) -> AppResult<()> {
+    state.supervisor.restart(service)
 }
 
 #[tauri::command]
-pub async fn reset_conversation(request: ResetRequest) -> Result<String, String> {
-    make_simple_post_request("/reset", &request)
-        .await
-        .map(|_| "Conversation réinitialisée".to_string())
+pub fn services_status(state: State<'_, AppState>) -> Vec<ServiceStatus> {
+    state.supervisor.status()
 }
 
 #[tauri::command]
-pub async fn check_services() -> Result<String, String> {
-    check_service_health().await
+pub fn get_settings(state: State<'_, AppState>) -> Settings {
+    state.settings.get()
 }
 
+/// Les changements de port ne prennent effet qu'au prochain démarrage du service concerné.
 #[tauri::command]
-pub async fn save_character(character: Character) -> Result<String, String> {
-    make_simple_post_request("/save-character", &character)
+pub fn update_settings(state: State<'_, AppState>, settings: Settings) -> AppResult<Settings> {
+    state.settings.update(settings)
+}
+
+/// `since` est le `seq` de la dernière ligne déjà reçue ; `level` le niveau minimal.
+#[tauri::command]
+pub fn get_service_logs(
+    state: State<'_, AppState>,
+    service: ServiceKind,
+    since: Option<u64>,
+    level: Option<LogLevel>,
+) -> Vec<LogLine> {
+    state.supervisor.logs(service, since, level)
+}
+
+#[tauri::command]
+pub fn system_status(state: State<'_, AppState>) -> SystemStatus {
+    state.supervisor.system_status()
+}
+
+fn go_url(state: &AppState, endpoint: &str) -> String {
+    format!("{}{}", state.supervisor.base_url(ServiceKind::Go), endpoint)
+}
+
+#[tauri::command]
+pub async fn ask_question(state: State<'_, AppState>, request: AskRequest) -> AppResult<AskResponse> {
+    state
+        .http
+        .post_json(ServiceKind::Go, &go_url(&state, "/ask"), &request, Retry::ConnectOnly)
         .await
-        .map(|_| "Personnage sauvegardé".to_string())
 }
 
 #[tauri::command]
-pub fn load_characters() -> Result<Vec<Character>, String> {
+pub async fn reset_conversation(state: State<'_, AppState>, request: ResetRequest) -> AppResult<String> {
+    file_utils::delete_chat_history(&state.vault, &request.character_id, &request.user_id)
+        .map(|_| t(Msg::ConversationReset).to_string())
+}
+
+#[tauri::command]
+pub async fn check_services(state: State<'_, AppState>) -> AppResult<ServiceHealth> {
+    let health = Arc::clone(&state.health);
+    let report = tauri::async_runtime::spawn_blocking(move || health.refresh()).await?;
+
+    report
+        .services
+        .into_iter()
+        .find(|s| s.service == ServiceKind::Go)
+        .ok_or_else(|| AppError::backend(ServiceKind::Go.label(), None, t(Msg::UnknownService)))
+}
+
+/// L'application tient seule `characters.json`, en ligne comme hors ligne : passer par
+/// l'API Go ferait dépendre les champs conservés de la version de son modèle.
+#[tauri::command]
+pub fn save_character(character: Character) -> AppResult<String> {
+    file_utils::update_character(character).map(|_| t(Msg::CharacterSaved).to_string())
+}
+
+#[tauri::command]
+pub fn load_characters() -> AppResult<Vec<Character>> {
     read_characters_file()
 }
 
 #[tauri::command]
-pub fn copy_image_to_path(file_name: String, data: Vec<u8>) -> Result<(), String> {
-    copy_image_file(file_name, data)
+pub fn store_image_asset(data: Vec<u8>) -> AppResult<StoredAsset> {
+    assets::store_image(&data)
+}
+
+#[tauri::command]
+pub fn gc_image_assets(state: State<'_, AppState>) -> AppResult<usize> {
+    assets::collect_garbage(&state.vault)
 }
 
 #[tauri::command]
-pub fn delete_character(id: String) -> Result<(), String> {
+pub fn delete_character(id: String) -> AppResult<()> {
     delete_character_from_file(&id)
 }
 
 #[tauri::command]
-pub fn update_character(character: Character) -> Result<(), String> {
+pub fn update_character(character: Character) -> AppResult<()> {
     crate::file_utils::update_character(character)
 }
 
 #[tauri::command]
-pub fn load_personas() -> Result<Vec<Persona>, String> {
-    file_utils::read_personas_file()
+pub fn load_personas(state: State<'_, AppState>) -> AppResult<Vec<Persona>> {
+    file_utils::read_personas_file(&state.vault)
+}
+
+#[tauri::command]
+pub fn save_persona(state: State<'_, AppState>, persona: Persona) -> AppResult<String> {
+    file_utils::update_persona(&state.vault, persona)
+        .map(|_| t(Msg::PersonaSaved).to_string())
+}
+
+#[tauri::command]
+pub fn delete_persona(state: State<'_, AppState>, persona_id: String) -> AppResult<()> {
+    file_utils::delete_persona_from_file(&state.vault, &persona_id)
+}
+
+#[tauri::command]
+pub fn update_persona(state: State<'_, AppState>, persona: Persona) -> AppResult<()> {
+    file_utils::update_persona(&state.vault, persona)
+}
+
+#[tauri::command]
+pub async fn chat_with_character(state: State<'_, AppState>, input: String,character_id: String,persona_id: String,history: Vec<ChatMessage>,) -> AppResult<String> {
+    chat::send_message(&state, &input, &character_id, &persona_id, history).await
+}
+
+#[tauri::command]
+pub fn load_group_chats() -> AppResult<Vec<GroupChat>> {
+    group::read_groups_file()
+}
+
+#[tauri::command]
+pub fn save_group_chat(state: State<'_, AppState>, group: GroupChat) -> AppResult<GroupChat> {
+    group::save_group(&state.vault, group)
+}
+
+#[tauri::command]
+pub fn delete_group_chat(state: State<'_, AppState>, group_id: String) -> AppResult<()> {
+    group::delete_group(&state.vault, &group_id)
+}
+
+#[tauri::command]
+pub fn load_group_history(state: State<'_, AppState>, group_id: String) -> AppResult<Vec<ChatMessage>> {
+    group::read_group_history(&state.vault, &group_id)
+}
+
+/// Sans `speaker_id`, le personnage qui répond est choisi selon la stratégie du groupe.
+#[tauri::command]
+pub async fn send_group_message(
+    state: State<'_, AppState>,
+    group_id: String,
+    input: Option<String>,
+    speaker_id: Option<String>,
+) -> AppResult<ChatMessage> {
+    group::send_group_message(&state, &group_id, input.as_deref(), speaker_id.as_deref()).await
+}
+
+#[tauri::command]
+pub fn load_relationship_state(
+    state: State<'_, AppState>,
+    character_id: String,
+    persona_id: String,
+) -> AppResult<RelationshipState> {
+    Ok(relationship::read_relationship(&state.vault, &character_id, &persona_id)?.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn reset_relationship_state(state: State<'_, AppState>, character_id: String, persona_id: String) -> AppResult<()> {
+    relationship::reset_relationship(&state.vault, &character_id, &persona_id)
 }
 
 #[tauri::command]
-pub fn save_persona(persona: Persona) -> Result<String, String> {
-    file_utils::update_persona(persona)
-        .map(|_| "Persona sauvegardée".to_string())
+pub fn load_scene_state(state: State<'_, AppState>, session: ChatSession) -> AppResult<SceneState> {
+    Ok(scene::read_scene(&state.vault, &session)?.unwrap_or_default())
 }
 
 #[tauri::command]
-pub fn delete_persona(persona_id: String) -> Result<(), String> {
-    file_utils::delete_persona_from_file(&persona_id)
+pub fn update_scene_state(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session: ChatSession,
+    scene: SceneState,
+) -> AppResult<SceneState> {
+    let scene = scene::write_scene(&state.vault, &session, scene)?;
+    scene::notify(&app, session, scene.clone());
+    Ok(scene)
 }
 
 #[tauri::command]
-pub fn copy_image_to_persona(file_name: String, data: Vec<u8>) -> Result<(), String> {
-    file_utils::copy_persona_image_file(file_name, data)
+pub fn list_character_presets() -> Vec<CharacterPreset> {
+    personality::builtin_presets()
 }
 
+/// Historique de la conversation, commencé par une salutation s'il était vide. Un
+/// personnage sans persona par défaut retient celle-ci, comme avec `set_default_persona`.
 #[tauri::command]
-pub fn update_persona(persona: Persona) -> Result<(), String> {
-    file_utils::update_persona(persona)
+pub fn start_chat_session(
+    state: State<'_, AppState>,
+    character_id: String,
+    persona_id: String,
+    greeting: Option<usize>,
+) -> AppResult<Vec<ChatMessage>> {
+    let mut character = load_character_by_id(character_id)?;
+    let history = chat::start_session(&state.vault, &character, &persona_id, greeting)?;
+
+    if character.default_persona_id.is_none() {
+        character.default_persona_id = Some(persona_id);
+        file_utils::update_character(character)?;
+    }
+    Ok(history)
 }
 
+/// `None` oublie la persona par défaut du personnage.
 #[tauri::command]
-pub async fn chat_with_character(input: String,character_id: String,persona_id: String,history: Vec<ChatMessage>,) -> Result<String, String> {
-    let ask_request = AskRequestForChat {
-        question: input,
-        character_id,
-        user_id: persona_id,
-        model: "dolphin-mistral".to_string(),
-        memory: history,
-    };
+pub fn set_default_persona(
+    state: State<'_, AppState>,
+    character_id: String,
+    persona_id: Option<String>,
+) -> AppResult<Character> {
+    if let Some(id) = &persona_id {
+        load_persona_by_id(state, id.clone())?;
+    }
+
+    let mut character = load_character_by_id(character_id)?;
+    character.default_persona_id = persona_id;
+    file_utils::update_character(character.clone())?;
+    Ok(character)
+}
+
+/// Brouillon de personnage rédigé par le modèle local, à relire avant `update_character`.
+#[tauri::command]
+pub async fn generate_character_draft(
+    state: State<'_, AppState>,
+    concept: String,
+    tags: Option<Vec<String>>,
+) -> AppResult<Character> {
+    wizard::generate_character_draft(&state, &concept, &tags.unwrap_or_default()).await
+}
 
-    match make_http_request::<AskRequestForChat, AskResponse>("/ask", &ask_request).await {
-        Ok(response) => Ok(response.answer),
-        Err(e) => Err(format!("Erreur LLM: {}", e)),
+/// Remplit la personnalité d'un personnage en cours de création ; l'enregistrement reste
+/// à faire avec `save_character`.
+#[tauri::command]
+pub fn apply_character_preset(character: Character, preset_id: String) -> AppResult<Character> {
+    personality::apply_preset(character, &preset_id)
+}
+
+#[tauri::command]
+pub fn list_scene_presets() -> AppResult<Vec<ScenePreset>> {
+    presets::list_presets()
+}
+
+#[tauri::command]
+pub fn save_scene_preset(preset: ScenePreset) -> AppResult<ScenePreset> {
+    presets::save_preset(preset)
+}
+
+#[tauri::command]
+pub fn delete_scene_preset(preset_id: String) -> AppResult<()> {
+    presets::delete_preset(&preset_id)
+}
+
+/// Change l'ambiance en cours de conversation ; elle s'applique dès la réplique suivante.
+#[tauri::command]
+pub fn set_scene_preset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session: ChatSession,
+    preset_id: Option<String>,
+) -> AppResult<SceneState> {
+    let scene = scene::set_preset(&state.vault, &session, preset_id.as_deref())?;
+    scene::notify(&app, session, scene.clone());
+    Ok(scene)
+}
+
+#[tauri::command]
+pub async fn narrate(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session: ChatSession,
+    direction: Option<String>,
+) -> AppResult<Narration> {
+    let narration = scene::narrate(&state, &session, direction.as_deref()).await?;
+    if narration.scene_changed {
+        scene::notify(&app, session, narration.scene.clone());
     }
+    Ok(narration)
 }
 
 #[tauri::command]
-pub fn load_character_by_id(id: String) -> Result<Character, String> {
+pub fn load_character_by_id(id: String) -> AppResult<Character> {
     let characters = read_characters_file()?;
     
     characters
         .into_iter()
         .find(|c| c.id == id)
-        .ok_or_else(|| format!("Personnage avec l'ID '{}' non trouvé", id))
+        .ok_or_else(|| AppError::not_found(Entity::Character, id))
 }
 
 #[tauri::command]
-pub fn load_persona_by_id(id: String) -> Result<Persona, String> {
-    let personas = file_utils::read_personas_file()?;
+pub fn load_persona_by_id(state: State<'_, AppState>, id: String) -> AppResult<Persona> {
+    let personas = file_utils::read_personas_file(&state.vault)?;
     
     personas
         .into_iter()
         .find(|p| p.id == id)
-        .ok_or_else(|| format!("Persona avec l'ID '{}' non trouvé", id))
+        .ok_or_else(|| AppError::not_found(Entity::Persona, id))
 }
 
 #[tauri::command]
-pub async fn check_services_status() -> Result<String, String> {
-    services::check_all_services_health().await
+pub async fn check_services_status(state: State<'_, AppState>) -> AppResult<HealthReport> {
+    let health = Arc::clone(&state.health);
+    Ok(tauri::async_runtime::spawn_blocking(move || health.refresh()).await?)
 }
 
 #[tauri::command]
-pub fn load_recent_chats() -> Result<Vec<RecentChat>, String> {
-    let path = std::path::Path::new("data/history");
+pub fn load_recent_chats(state: State<'_, AppState>) -> AppResult<Vec<RecentChat>> {
     let mut chats = vec![];
 
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) {
-                  
-                    let parts: Vec<&str> = file_stem.split('_').collect();
-                    if parts.len() >= 2 {
-                        let character_id = parts[0].to_string();
-                        let persona_id = parts[1..].join("_"); 
+    for (character_id, persona_id) in file_utils::list_chat_histories() {
+        if let Ok(messages) = file_utils::read_chat_history(&state.vault, &character_id, &persona_id) {
+            if !messages.is_empty() {
+                
+                let last_message = messages.last().unwrap();
+                let timestamp = if let Some(ts) = last_message.timestamp.as_deref() {
+                    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(ts) {
                         
-                       
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if let Ok(messages) = serde_json::from_str::<Vec<ChatMessage>>(&content) {
-                                if !messages.is_empty() {
-                                    
-                                    let last_message = messages.last().unwrap();
-                                    let timestamp = if let Some(ts) = last_message.timestamp.as_deref() {
-                                        if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                            
-                                            (datetime.timestamp() * 1000 + datetime.timestamp_subsec_millis() as i64) as u64
-                                            } else {
-                                                0
-                                            }
-                                        } else {
-                                            0
-                                        };
-                                    
-                                    let (name, img) = if let Ok(characters) = read_characters_file() {
-                                        if let Some(character) = characters.iter().find(|c| c.id == character_id) {
-                                            (character.name.clone(), character.img.clone())
-                                        } else {
-                                            (character_id.clone(), String::new())
-                                        }
-                                    } else {
-                                        (character_id.clone(), String::new())
-                                    };
-                                    
-                                    chats.push(RecentChat {
-                                        character_id,
-                                        persona_id,
-                                        name,
-                                        img: Some(img),
-                                        last_used: timestamp,
-                                    });
-                                }
-                            }
+                        (datetime.timestamp() * 1000 + datetime.timestamp_subsec_millis() as i64) as u64
+                        } else {
+                            0
                         }
+                    } else {
+                        0
+                    };
+                
+                let (name, img) = if let Ok(characters) = read_characters_file() {
+                    if let Some(character) = characters.iter().find(|c| c.id == character_id) {
+                        (character.name.clone(), character.img.clone())
+                    } else {
+                        (character_id.clone(), String::new())
                     }
-                }
+                } else {
+                    (character_id.clone(), String::new())
+                };
+                
+                chats.push(RecentChat {
+                    character_id,
+                    persona_id,
+                    name,
+                    img: Some(img),
+                    last_used: timestamp,
+                });
             }
         }
     }
@@ -201,23 +415,79 @@ pub fn load_recent_chats() -> Result<Vec<RecentChat>, String> {
 
 
 #[tauri::command]
-pub fn load_chat_history(character_id: String, persona_id: String) -> Result<Vec<ChatMessage>, String> {
-    let filename = format!("data/history/{}_{}.json", character_id, persona_id);
-    if std::path::Path::new(&filename).exists() {
-        let content = std::fs::read_to_string(&filename).map_err(|e| e.to_string())?;
-        let messages: Vec<ChatMessage> = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-        Ok(messages)
-    } else {
-        Ok(vec![]) 
-    }
+pub fn load_chat_history(state: State<'_, AppState>, character_id: String, persona_id: String) -> AppResult<Vec<ChatMessage>> {
+    file_utils::read_chat_history(&state.vault, &character_id, &persona_id)
 }
 
 
 #[tauri::command]
-pub fn delete_chat_history(character_id: String, persona_id: String) -> Result<(), String> {
-    let filename = format!("data/history/{}_{}.json", character_id, persona_id);
-    if std::path::Path::new(&filename).exists() {
-        std::fs::remove_file(&filename).map_err(|e| e.to_string())?;
-    }
-    Ok(())
-}
\ No newline at end of file
+pub fn delete_chat_history(state: State<'_, AppState>, character_id: String, persona_id: String) -> AppResult<()> {
+    file_utils::delete_chat_history(&state.vault, &character_id, &persona_id)
+}
+
+#[tauri::command]
+pub fn export_library(state: State<'_, AppState>, dest_path: String) -> AppResult<LibraryManifest> {
+    library::export_library(&state.vault, std::path::Path::new(&dest_path))
+}
+
+#[tauri::command]
+pub fn import_library(state: State<'_, AppState>, source_path: String, options: ImportOptions) -> AppResult<ImportReport> {
+    library::import_library(&state.vault, std::path::Path::new(&source_path), &options)
+}
+
+#[tauri::command]
+pub fn export_chat_transcript(
+    state: State<'_, AppState>,
+    character_id: String,
+    persona_id: String,
+    format: TranscriptFormat,
+    dest_path: String,
+) -> AppResult<()> {
+    transcript::export_transcript(&state.vault, &character_id, &persona_id, format, std::path::Path::new(&dest_path))
+}
+
+#[tauri::command]
+pub fn list_snapshots() -> AppResult<Vec<SnapshotInfo>> {
+    snapshots::list_snapshots()
+}
+
+#[tauri::command]
+pub fn diff_snapshot(state: State<'_, AppState>, snapshot_id: String) -> AppResult<SnapshotDiff> {
+    snapshots::diff_snapshot(&state.vault, &snapshot_id)
+}
+
+#[tauri::command]
+pub fn restore_character_from_snapshot(state: State<'_, AppState>, snapshot_id: String, character_id: String) -> AppResult<()> {
+    snapshots::restore_character(&state.vault, &snapshot_id, &character_id)
+}
+
+#[tauri::command]
+pub fn restore_chat_from_snapshot(state: State<'_, AppState>, snapshot_id: String, character_id: String, persona_id: String) -> AppResult<()> {
+    snapshots::restore_chat(&state.vault, &snapshot_id, &character_id, &persona_id)
+}
+
+#[tauri::command]
+pub fn vault_status(state: State<'_, AppState>) -> VaultStatus {
+    state.vault.status()
+}
+
+#[tauri::command]
+pub fn enable_vault(state: State<'_, AppState>, passphrase: String) -> AppResult<()> {
+    state.vault.enable(&passphrase, &file_utils::vault_protected_files())?;
+    snapshots::seal_snapshots(&state.vault)
+}
+
+#[tauri::command]
+pub fn unlock_vault(state: State<'_, AppState>, passphrase: String) -> AppResult<()> {
+    state.vault.unlock(&passphrase)
+}
+
+#[tauri::command]
+pub fn lock_vault(state: State<'_, AppState>) {
+    state.vault.lock()
+}
+
+#[tauri::command]
+pub fn change_vault_passphrase(state: State<'_, AppState>, old_passphrase: String, new_passphrase: String) -> AppResult<()> {
+    state.vault.change_passphrase(&old_passphrase, &new_passphrase)
+}
@@ -0,0 +1,244 @@
+use crate::i18n::{t, tf, Msg};
+use crate::types::ServiceStartError;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::Path;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Objet manquant dans une erreur `NotFound`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    Character,
+    Persona,
+    Chat,
+    Snapshot,
+    File,
+    Group,
+    Preset,
+    CharacterPreset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoAction {
+    Read,
+    Write,
+    CreateDir,
+    Delete,
+}
+
+/// Erreur renvoyée par toutes les commandes. Sérialisée en
+/// `{ "code": "NOT_FOUND", "message": "...", "context": { ... } }` : l'interface se base
+/// sur `code`, stable, et affiche `message`, rédigé dans la langue des réglages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    NotFound { entity: Entity, id: String },
+    Validation { field: Option<String>, reason: String },
+    Io { action: IoAction, path: String, reason: String },
+    Parse { source: String, reason: String },
+    Backend { service: String, status: Option<u16>, reason: String },
+    Timeout { operation: String },
+    Cancelled { operation: String },
+    Conflict { reason: String },
+}
+
+impl AppError {
+    pub fn not_found(entity: Entity, id: impl Into<String>) -> Self {
+        AppError::NotFound { entity, id: id.into() }
+    }
+
+    pub fn validation(reason: impl Into<String>) -> Self {
+        AppError::Validation {
+            field: None,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn invalid_field(field: &str, reason: impl Into<String>) -> Self {
+        AppError::Validation {
+            field: Some(field.to_string()),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn io(action: IoAction, path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        AppError::Io {
+            action,
+            path: path.as_ref().display().to_string(),
+            reason: error.to_string(),
+        }
+    }
+
+    pub fn read(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        Self::io(IoAction::Read, path, error)
+    }
+
+    pub fn write(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        Self::io(IoAction::Write, path, error)
+    }
+
+    pub fn create_dir(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        Self::io(IoAction::CreateDir, path, error)
+    }
+
+    pub fn delete(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        Self::io(IoAction::Delete, path, error)
+    }
+
+    /// `source` désigne ce qui a été lu ou écrit : un chemin, une entrée d'archive, une réponse.
+    pub fn parse(source: impl Into<String>, error: impl fmt::Display) -> Self {
+        AppError::Parse {
+            source: source.into(),
+            reason: error.to_string(),
+        }
+    }
+
+    pub fn backend(service: impl Into<String>, status: Option<u16>, reason: impl Into<String>) -> Self {
+        AppError::Backend {
+            service: service.into(),
+            status,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn cancelled(operation: impl Into<String>) -> Self {
+        AppError::Cancelled {
+            operation: operation.into(),
+        }
+    }
+
+    pub fn conflict(reason: impl Into<String>) -> Self {
+        AppError::Conflict { reason: reason.into() }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "NOT_FOUND",
+            AppError::Validation { .. } => "VALIDATION",
+            AppError::Io { .. } => "IO",
+            AppError::Parse { .. } => "PARSE",
+            AppError::Backend { .. } => "BACKEND",
+            AppError::Timeout { .. } => "TIMEOUT",
+            AppError::Cancelled { .. } => "CANCELLED",
+            AppError::Conflict { .. } => "CONFLICT",
+        }
+    }
+
+    pub fn context(&self) -> Value {
+        match self {
+            AppError::NotFound { entity, id } => json!({ "entity": entity, "id": id }),
+            AppError::Validation { field, .. } => json!({ "field": field }),
+            AppError::Io { action, path, reason } => json!({ "action": action, "path": path, "reason": reason }),
+            AppError::Parse { source, reason } => json!({ "source": source, "reason": reason }),
+            AppError::Backend { service, status, reason } => {
+                json!({ "service": service, "status": status, "reason": reason })
+            }
+            AppError::Timeout { operation } | AppError::Cancelled { operation } => json!({ "operation": operation }),
+            AppError::Conflict { .. } => json!({}),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            AppError::NotFound { entity, id } => {
+                let label = match entity {
+                    Entity::Character => t(Msg::EntityCharacter),
+                    Entity::Persona => t(Msg::EntityPersona),
+                    Entity::Chat => t(Msg::EntityChat),
+                    Entity::Snapshot => t(Msg::EntitySnapshot),
+                    Entity::File => t(Msg::EntityFile),
+                    Entity::Group => t(Msg::EntityGroup),
+                    Entity::Preset => t(Msg::EntityPreset),
+                    Entity::CharacterPreset => t(Msg::EntityCharacterPreset),
+                };
+                tf(Msg::NotFound, &[&label, id])
+            }
+            AppError::Validation { reason, .. } | AppError::Conflict { reason } => reason.clone(),
+            AppError::Io { action, path, reason } => {
+                let label = match action {
+                    IoAction::Read => t(Msg::IoRead),
+                    IoAction::Write => t(Msg::IoWrite),
+                    IoAction::CreateDir => t(Msg::IoCreateDir),
+                    IoAction::Delete => t(Msg::IoDelete),
+                };
+                format!("{} {}: {}", label, path, reason)
+            }
+            AppError::Parse { source, reason } => tf(Msg::ParseFailed, &[source, reason]),
+            AppError::Backend { service, status: Some(status), reason } => {
+                tf(Msg::BackendFailedWithStatus, &[service, status, reason])
+            }
+            AppError::Backend { service, reason, .. } => tf(Msg::BackendFailed, &[service, reason]),
+            AppError::Timeout { operation } => tf(Msg::TimedOut, &[operation]),
+            AppError::Cancelled { operation } => tf(Msg::Cancelled, &[operation]),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("context", &self.context())?;
+        state.end()
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        let operation = e.url().map(|u| u.to_string()).unwrap_or_else(|| t(Msg::HttpRequest).to_string());
+        if e.is_timeout() {
+            AppError::Timeout { operation }
+        } else if e.is_decode() {
+            AppError::parse(operation, e)
+        } else {
+            AppError::backend("API", e.status().map(|s| s.as_u16()), e.to_string())
+        }
+    }
+}
+
+impl From<ServiceStartError> for AppError {
+    fn from(e: ServiceStartError) -> Self {
+        match &e {
+            ServiceStartError::NotReady { service, .. } => AppError::Timeout {
+                operation: tf(Msg::ServiceStartup, &[&service.label()]),
+            },
+            ServiceStartError::PortUnavailable { .. } => AppError::conflict(e.to_string()),
+            ServiceStartError::ExecutableNotFound { service, .. }
+            | ServiceStartError::SpawnFailed { service, .. }
+            | ServiceStartError::ExitedDuringStartup { service }
+            | ServiceStartError::Crashed { service, .. } => {
+                AppError::backend(service.label(), None, format!("{} — {}", e, e.hint()))
+            }
+        }
+    }
+}
+
+/// Échec du runtime (fenêtre, chemins, événements) : rien n'a été annulé par l'utilisateur.
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::backend("Tauri", None, e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tauri_errors_are_backend_failures_with_their_message() {
+        let error = AppError::from(tauri::Error::Io(std::io::Error::other("disque plein")));
+
+        assert_eq!(error.code(), "BACKEND");
+        assert!(error.context()["reason"].as_str().unwrap().contains("disque plein"));
+    }
+}
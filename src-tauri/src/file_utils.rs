@@ -1,48 +1,56 @@
 use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
 
-use crate::types::{Character, Persona};
+use crate::error::{AppError, AppResult, Entity};
+use crate::i18n::{tf, Msg};
+use crate::personality;
+use crate::types::{Character, ChatMessage, Persona};
+use crate::vault::{Vault, ENCRYPTED_EXTENSION};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub const CHARACTERS_FILE_PATH: &str = "data/characters.json";
 pub const ASSETS_PATH: &str = "../tauri-ui/public/assets/characters";
 pub const HISTORY_DIR: &str = "data/history";
+pub const GROUP_HISTORY_DIR: &str = "data/history/groups";
+pub const GROUPS_FILE_PATH: &str = "data/groups.json";
+pub const SCENES_DIR: &str = "data/scenes";
+pub const SCENE_PRESETS_FILE_PATH: &str = "data/scene_presets.json";
+pub const RELATIONSHIPS_DIR: &str = "data/history/relationships";
 pub const PERSONAS_FILE_PATH: &str = "data/personas.json"; 
 pub const PERSONA_ASSETS_PATH: &str = "../tauri-ui/public/assets/personas";
-
-pub fn read_characters_file() -> Result<Vec<Character>, String> {
+pub const ASSET_STORE_PATH: &str = "../tauri-ui/public/assets/store";
+pub const ASSET_STORE_URL: &str = "/assets/store";
+pub const RECENT_CHATS_FILE_PATH: &str = "data/recent_chats.json";
+pub const SETTINGS_FILE_PATH: &str = "data/settings.json";
+pub const PUBLIC_DIR: &str = "../tauri-ui/public";
+pub const DATA_DIR: &str = "data";
+pub const SNAPSHOTS_DIR: &str = "data/snapshots";
+pub const VAULT_FILE_PATH: &str = "data/vault.json";
+pub const LOGS_DIR: &str = "data/logs";
+
+pub fn read_characters_file() -> AppResult<Vec<Character>> {
 
     let content = fs::read_to_string(CHARACTERS_FILE_PATH)
-        .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+        .map_err(|e| AppError::read(CHARACTERS_FILE_PATH, e))?;
     
     serde_json::from_str::<Vec<Character>>(&content)
-        .map_err(|e| format!("Erreur parsing JSON: {}", e))
+        .map_err(|e| AppError::parse(CHARACTERS_FILE_PATH, e))
 }
 
 
 
-pub fn write_characters_file(characters: &[serde_json::Value]) -> Result<(), String> {
+pub fn write_characters_file(characters: &[serde_json::Value]) -> AppResult<()> {
 
     let updated = serde_json::to_string_pretty(characters)
-        .map_err(|e| format!("Erreur sérialisation JSON: {}", e))?;
+        .map_err(|e| AppError::parse(CHARACTERS_FILE_PATH, e))?;
 
     fs::write(CHARACTERS_FILE_PATH, updated)
-        .map_err(|e| format!("Erreur écriture fichier: {}", e))
+        .map_err(|e| AppError::write(CHARACTERS_FILE_PATH, e))
 }
 
 
 
-pub fn copy_image_file(file_name: String, data: Vec<u8>) -> Result<(), String> {
-
-    let dest = PathBuf::from(ASSETS_PATH).join(&file_name);
-
-    if !dest.parent().unwrap().exists() {
-        return Err("Le dossier assets/characters n'existe pas".into());
-    }
-
-    fs::write(dest, data).map_err(|e| format!("Erreur écriture fichier : {}", e))
-}
-
 pub fn remove_character_image(img_path: &str) {
 
     if img_path != "placeholder.png" {
@@ -69,13 +77,13 @@ pub fn remove_character_history(character_id: &str) {
     }
 }
 
-pub fn delete_character_from_file(id: &str) -> Result<(), String> {
+pub fn delete_character_from_file(id: &str) -> AppResult<()> {
 
     let path = Path::new(CHARACTERS_FILE_PATH);
     
-    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let data = fs::read_to_string(path).map_err(|e| AppError::read(path, e))?;
     let mut characters: Vec<serde_json::Value> = 
-        serde_json::from_str(&data).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| AppError::parse(CHARACTERS_FILE_PATH, e))?;
     
     let original_len = characters.len();
     let mut removed_img: Option<String> = None;
@@ -92,7 +100,7 @@ pub fn delete_character_from_file(id: &str) -> Result<(), String> {
     });
 
     if characters.len() == original_len {
-        return Err("Personnage non trouvé".into());
+        return Err(AppError::not_found(Entity::Character, id));
     }
 
    
@@ -109,8 +117,10 @@ pub fn delete_character_from_file(id: &str) -> Result<(), String> {
     Ok(())
 }
 
-pub fn update_character(updated_char: Character) -> Result<(), String> {
-    
+pub fn update_character(updated_char: Character) -> AppResult<()> {
+    validate_id("id", &updated_char.id)?;
+    personality::validate(&updated_char)?;
+
     let mut characters = read_characters_file()?;
 
     
@@ -137,31 +147,20 @@ pub fn update_character(updated_char: Character) -> Result<(), String> {
     write_characters_file(&values)
 }
 
-pub fn read_personas_file() -> Result<Vec<Persona>, String> {
-    let content = fs::read_to_string(PERSONAS_FILE_PATH)
-        .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+pub fn read_personas_file(vault: &Vault) -> AppResult<Vec<Persona>> {
+    let content = vault
+        .read(Path::new(PERSONAS_FILE_PATH))?
+        .ok_or_else(|| AppError::not_found(Entity::File, PERSONAS_FILE_PATH))?;
     
-    serde_json::from_str::<Vec<Persona>>(&content)
-        .map_err(|e| format!("Erreur parsing JSON: {}", e))
+    serde_json::from_slice::<Vec<Persona>>(&content)
+        .map_err(|e| AppError::parse(PERSONAS_FILE_PATH, e))
 }
 
-pub fn write_personas_file(personas: &Vec<Persona>) -> Result<(), String> {
+pub fn write_personas_file(vault: &Vault, personas: &Vec<Persona>) -> AppResult<()> {
     let updated = serde_json::to_string_pretty(personas)
-        .map_err(|e| format!("Erreur sérialisation JSON: {}", e))?;
-
-    fs::write(PERSONAS_FILE_PATH, updated)
-        .map_err(|e| format!("Erreur écriture fichier: {}", e))
-}
-
-pub fn copy_persona_image_file(file_name: String, data: Vec<u8>) -> Result<(), String> {
-    let dest = PathBuf::from(PERSONA_ASSETS_PATH).join(&file_name);
+        .map_err(|e| AppError::parse(PERSONAS_FILE_PATH, e))?;
 
-    if !dest.parent().unwrap().exists() {
-        fs::create_dir_all(dest.parent().unwrap())
-            .map_err(|e| format!("Erreur création dossier: {}", e))?;
-    }
-
-    fs::write(dest, data).map_err(|e| format!("Erreur écriture fichier : {}", e))
+    vault.write(Path::new(PERSONAS_FILE_PATH), updated.as_bytes())
 }
 
 pub fn remove_persona_image(img_path: &str) {
@@ -175,12 +174,8 @@ pub fn remove_persona_image(img_path: &str) {
 }
 
 
-pub fn delete_persona_from_file(id: &str) -> Result<(), String> {
-    let path = Path::new(PERSONAS_FILE_PATH);
-    
-    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let mut personas: Vec<Persona> = 
-        serde_json::from_str(&data).map_err(|e| e.to_string())?;
+pub fn delete_persona_from_file(vault: &Vault, id: &str) -> AppResult<()> {
+    let mut personas = read_personas_file(vault)?;
     
     let original_len = personas.len();
     let mut removed_img: Option<String> = None;
@@ -195,7 +190,7 @@ pub fn delete_persona_from_file(id: &str) -> Result<(), String> {
     });
 
     if personas.len() == original_len {
-        return Err("Persona non trouvé".into());
+        return Err(AppError::not_found(Entity::Persona, id));
     }
 
     
@@ -204,14 +199,23 @@ pub fn delete_persona_from_file(id: &str) -> Result<(), String> {
     }
 
     
-    write_personas_file(&personas)?;
+    write_personas_file(vault, &personas)?;
+
+    // Les personnages qui l'avaient comme persona par défaut l'oublient.
+    for mut character in read_characters_file()? {
+        if character.default_persona_id.as_deref() == Some(id) {
+            character.default_persona_id = None;
+            update_character(character)?;
+        }
+    }
 
     Ok(())
 }
 
 
-pub fn update_persona(updated_persona: Persona) -> Result<(), String> {
-    let mut personas = read_personas_file()?;
+pub fn update_persona(vault: &Vault, updated_persona: Persona) -> AppResult<()> {
+    validate_id("id", &updated_persona.id)?;
+    let mut personas = read_personas_file(vault)?;
 
     let mut found = false;
     for persona in &mut personas {
@@ -226,5 +230,203 @@ pub fn update_persona(updated_persona: Persona) -> Result<(), String> {
         personas.push(updated_persona);
     }
 
-    write_personas_file(&personas)
-}
\ No newline at end of file
+    write_personas_file(vault, &personas)
+}
+
+/// Les identifiants servent de noms de fichiers (historiques, groupes, scènes) : ils ne
+/// doivent ni sortir de leur dossier ni désigner un fichier caché.
+pub fn validate_id(field: &str, id: &str) -> AppResult<()> {
+    let safe = !id.is_empty()
+        && !id.starts_with('.')
+        && !id.contains(['/', '\\', ':'])
+        && !id.chars().any(char::is_control);
+    if safe {
+        Ok(())
+    } else {
+        Err(AppError::invalid_field(field, tf(Msg::InvalidId, &[&id])))
+    }
+}
+
+/// Identifiant tiré d'un nom libre (modèle, utilisateur) : `[a-z0-9-]` puis un horodatage,
+/// donc toujours accepté par `validate_id`. `fallback` sert si le nom n'en garde rien.
+pub fn id_from_name(name: &str, fallback: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() { fallback } else { &slug };
+    format!("{}_{}", slug, chrono::Local::now().timestamp_millis())
+}
+
+pub fn validate_session_ids(character_id: &str, persona_id: &str) -> AppResult<()> {
+    validate_id("character_id", character_id)?;
+    validate_id("persona_id", persona_id)
+}
+
+pub fn history_file_path(character_id: &str, persona_id: &str) -> PathBuf {
+    Path::new(HISTORY_DIR).join(format!("{}_{}.json", character_id, persona_id))
+}
+
+/// Un historique en clair peut subsister à côté de sa version chiffrée (écrit par une
+/// version antérieure des services) : on l'ajoute à la suite, et la prochaine écriture
+/// via le coffre le chiffre puis le supprime.
+pub fn read_chat_history(vault: &Vault, character_id: &str, persona_id: &str) -> AppResult<Vec<ChatMessage>> {
+    validate_session_ids(character_id, persona_id)?;
+    let path = history_file_path(character_id, persona_id);
+    let sealed = crate::vault::encrypted_path(&path);
+
+    let mut messages: Vec<ChatMessage> = if sealed.exists() {
+        serde_json::from_slice(&vault.open(&sealed)?).map_err(|e| AppError::parse(sealed.display().to_string(), e))?
+    } else {
+        vec![]
+    };
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| AppError::read(&path, e))?;
+        let plain: Vec<ChatMessage> =
+            serde_json::from_str(&content).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+        messages.extend(plain);
+    }
+
+    Ok(messages)
+}
+
+pub fn write_chat_history(vault: &Vault, character_id: &str, persona_id: &str, messages: &[ChatMessage]) -> AppResult<()> {
+    validate_session_ids(character_id, persona_id)?;
+    let path = history_file_path(character_id, persona_id);
+    let content = serde_json::to_string_pretty(messages)
+        .map_err(|e| AppError::parse(path.display().to_string(), e))?;
+
+    fs::create_dir_all(HISTORY_DIR).map_err(|e| AppError::create_dir(HISTORY_DIR, e))?;
+    vault.write(&path, content.as_bytes())
+}
+
+pub fn delete_chat_history(vault: &Vault, character_id: &str, persona_id: &str) -> AppResult<()> {
+    validate_session_ids(character_id, persona_id)?;
+    vault.remove(&history_file_path(character_id, persona_id))
+}
+
+pub fn append_chat_messages(vault: &Vault, character_id: &str, persona_id: &str, new_messages: Vec<ChatMessage>) -> AppResult<()> {
+    let mut messages = read_chat_history(vault, character_id, persona_id)?;
+    messages.extend(new_messages);
+    write_chat_history(vault, character_id, persona_id, &messages)
+}
+
+/// Couples (personnage, persona) de tous les historiques, chiffrés ou non.
+pub fn list_chat_histories() -> Vec<(String, String)> {
+    let character_ids: Vec<String> = read_characters_file()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    let mut sessions = vec![];
+
+    if let Ok(entries) = fs::read_dir(HISTORY_DIR) {
+        for entry in entries.flatten() {
+            let Ok(file_name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let stem = file_name
+                .strip_suffix(&format!(".{}", ENCRYPTED_EXTENSION))
+                .unwrap_or(&file_name);
+
+            if let Some(session) = stem.strip_suffix(".json").and_then(|s| split_history_stem(s, &character_ids)) {
+                if !sessions.contains(&session) {
+                    sessions.push(session);
+                }
+            }
+        }
+    }
+
+    sessions
+}
+
+/// Sépare le nom `<character_id>_<persona_id>` d'un historique. Les identifiants ont
+/// eux-mêmes la forme `nom_horodatage` : on cherche d'abord le plus long identifiant de
+/// personnage connu en préfixe, sinon on coupe après le deuxième `_`.
+pub fn split_history_stem(stem: &str, character_ids: &[String]) -> Option<(String, String)> {
+    let known = character_ids
+        .iter()
+        .filter(|id| stem.len() > id.len() + 1 && stem.starts_with(id.as_str()) && stem[id.len()..].starts_with('_'))
+        .max_by_key(|id| id.len());
+    if let Some(id) = known {
+        return Some((id.clone(), stem[id.len() + 1..].to_string()));
+    }
+
+    let split = stem.match_indices('_').nth(1).map(|(i, _)| i).or_else(|| stem.find('_'))?;
+    let (character_id, persona_id) = (&stem[..split], &stem[split + 1..]);
+    if character_id.is_empty() || persona_id.is_empty() {
+        return None;
+    }
+    Some((character_id.to_string(), persona_id.to_string()))
+}
+
+/// Fichiers à chiffrer lors de l'activation du coffre : personas et historiques.
+pub fn vault_protected_files() -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from(PERSONAS_FILE_PATH)];
+    for dir in [HISTORY_DIR, GROUP_HISTORY_DIR, SCENES_DIR, RELATIONSHIPS_DIR] {
+        if let Ok(entries) = fs::read_dir(dir) {
+            files.extend(
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "json")),
+            );
+        }
+    }
+    files
+}
+
+pub fn public_asset_path(img: &str) -> PathBuf {
+    Path::new(PUBLIC_DIR).join(img.trim_start_matches('/'))
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_from_names_are_valid_slugs() {
+        let id = id_from_name("../Mira: la Cartographe/", "character");
+        assert!(id.starts_with("mira-la-cartographe_"));
+        assert!(validate_id("id", &id).is_ok());
+
+        assert!(id_from_name(" .. ", "character").starts_with("character_"));
+    }
+
+    #[test]
+    fn history_stem_splits_on_known_character_id() {
+        let ids = vec!["alice_1700000000000".to_string(), "alice".to_string()];
+
+        assert_eq!(
+            split_history_stem("alice_1700000000000_bob_1700000000001", &ids),
+            Some(("alice_1700000000000".to_string(), "bob_1700000000001".to_string()))
+        );
+    }
+
+    #[test]
+    fn history_stem_falls_back_to_name_timestamp_ids() {
+        assert_eq!(
+            split_history_stem("carol_12_dave_34", &[]),
+            Some(("carol_12".to_string(), "dave_34".to_string()))
+        );
+        assert_eq!(split_history_stem("carol_dave", &[]), Some(("carol".to_string(), "dave".to_string())));
+        assert_eq!(split_history_stem("carol", &[]), None);
+    }
+
+    #[test]
+    fn ids_must_be_plain_file_names() {
+        for id in ["alice_1700000000000", "élodie-2_12", "o'brien_3"] {
+            assert!(validate_id("id", id).is_ok(), "{id:?} refusé");
+        }
+        for id in ["", "..", ".hidden", "../data/vault", "a/b", "a\\b", "c:d", "a\nb"] {
+            assert!(validate_id("id", id).is_err(), "{id:?} accepté");
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::chat;
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils::{self, GROUPS_FILE_PATH, GROUP_HISTORY_DIR};
+use crate::http::Retry;
+use crate::i18n::{t, tf, Msg};
+use crate::presets;
+use crate::relationship;
+use crate::scene;
+use crate::types::{
+    AppState, AskRequestForChat, AskResponse, Character, ChatMessage, ChatSession, GroupChat, NextSpeakerRequest,
+    NextSpeakerResponse, ServiceKind, TurnStrategy,
+};
+use crate::vault::Vault;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn read_groups_file() -> AppResult<Vec<GroupChat>> {
+    match fs::read_to_string(GROUPS_FILE_PATH) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| AppError::parse(GROUPS_FILE_PATH, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(AppError::read(GROUPS_FILE_PATH, e)),
+    }
+}
+
+pub fn write_groups_file(groups: &[GroupChat]) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(groups).map_err(|e| AppError::parse(GROUPS_FILE_PATH, e))?;
+    fs::write(GROUPS_FILE_PATH, content).map_err(|e| AppError::write(GROUPS_FILE_PATH, e))
+}
+
+pub fn find_group(group_id: &str) -> AppResult<GroupChat> {
+    read_groups_file()?
+        .into_iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| AppError::not_found(Entity::Group, group_id))
+}
+
+/// Crée le groupe (un identifiant est attribué s'il n'en a pas) ou le remplace.
+pub fn save_group(vault: &Vault, mut group: GroupChat) -> AppResult<GroupChat> {
+    let mut seen = HashSet::new();
+    group.character_ids.retain(|id| seen.insert(id.clone()));
+    if group.character_ids.len() < 2 {
+        return Err(AppError::invalid_field("character_ids", t(Msg::GroupTooSmall)));
+    }
+
+    let characters = file_utils::read_characters_file()?;
+    if let Some(missing) = group.character_ids.iter().find(|id| !characters.iter().any(|c| &c.id == *id)) {
+        return Err(AppError::not_found(Entity::Character, missing));
+    }
+    if !file_utils::read_personas_file(vault)?.iter().any(|p| p.id == group.persona_id) {
+        return Err(AppError::not_found(Entity::Persona, &group.persona_id));
+    }
+
+    if group.id.trim().is_empty() {
+        group.id = format!("group_{}", chrono::Local::now().timestamp_millis());
+    }
+    file_utils::validate_id("id", &group.id)?;
+
+    let mut groups = read_groups_file()?;
+    match groups.iter_mut().find(|g| g.id == group.id) {
+        Some(existing) => *existing = group.clone(),
+        None => groups.push(group.clone()),
+    }
+    write_groups_file(&groups)?;
+    Ok(group)
+}
+
+pub fn delete_group(vault: &Vault, group_id: &str) -> AppResult<()> {
+    file_utils::validate_id("group_id", group_id)?;
+    let mut groups = read_groups_file()?;
+    let before = groups.len();
+    groups.retain(|g| g.id != group_id);
+    if groups.len() == before {
+        return Err(AppError::not_found(Entity::Group, group_id));
+    }
+
+    write_groups_file(&groups)?;
+    vault.remove(&scene::scene_path(&ChatSession::Group { group_id: group_id.to_string() })?)?;
+    vault.remove(&group_history_path(group_id))
+}
+
+pub fn group_history_path(group_id: &str) -> PathBuf {
+    Path::new(GROUP_HISTORY_DIR).join(format!("{}.json", group_id))
+}
+
+pub fn read_group_history(vault: &Vault, group_id: &str) -> AppResult<Vec<ChatMessage>> {
+    file_utils::validate_id("group_id", group_id)?;
+    let path = group_history_path(group_id);
+    match vault.read(&path)? {
+        Some(data) => serde_json::from_slice(&data).map_err(|e| AppError::parse(path.display().to_string(), e)),
+        None => Ok(vec![]),
+    }
+}
+
+fn write_group_history(vault: &Vault, group_id: &str, messages: &[ChatMessage]) -> AppResult<()> {
+    file_utils::validate_id("group_id", group_id)?;
+    let path = group_history_path(group_id);
+    let content = serde_json::to_string_pretty(messages).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+
+    fs::create_dir_all(GROUP_HISTORY_DIR).map_err(|e| AppError::create_dir(GROUP_HISTORY_DIR, e))?;
+    vault.write(&path, content.as_bytes())
+}
+
+pub fn append_group_messages(vault: &Vault, group_id: &str, new_messages: Vec<ChatMessage>) -> AppResult<()> {
+    let mut messages = read_group_history(vault, group_id)?;
+    messages.extend(new_messages);
+    write_group_history(vault, group_id, &messages)
+}
+
+/// Envoie `input` au groupe (ou, sans message, laisse la scène continuer) et renvoie la
+/// réplique du personnage désigné par `speaker_id` ou, à défaut, par la stratégie du groupe.
+/// L'historique du groupe est tenu ici : les services ne l'enregistrent pas.
+pub async fn send_group_message(
+    state: &AppState,
+    group_id: &str,
+    input: Option<&str>,
+    speaker_id: Option<&str>,
+) -> AppResult<ChatMessage> {
+    let group = find_group(group_id)?;
+    let input = input.map(str::trim).filter(|i| !i.is_empty());
+    let mut history = read_group_history(&state.vault, group_id)?;
+
+    let speaker = match speaker_id {
+        Some(id) if group.character_ids.iter().any(|c| c == id) => id.to_string(),
+        Some(id) => return Err(AppError::invalid_field("speaker_id", tf(Msg::GroupSpeakerNotMember, &[&id]))),
+        None => next_speaker(state, &group, input, &history).await?,
+    };
+
+    let mut request = chat::ask_request(state, input.unwrap_or_default(), &speaker, &group.persona_id, history.clone(), false)?;
+    request.group_id = Some(group.id.clone());
+    request.members = group.character_ids.iter().filter(|id| **id != speaker).cloned().collect();
+    request.scene = scene::read_scene(&state.vault, &ChatSession::Group { group_id: group.id.clone() })?;
+    request.scene_preset = presets::preset_for(request.scene.as_ref());
+
+    let url = format!("{}/ask", state.supervisor.base_url(ServiceKind::Go));
+    let answer = state
+        .http
+        .post_json::<AskRequestForChat, AskResponse>(ServiceKind::Go, &url, &request, Retry::ConnectOnly)
+        .await?
+        .answer;
+
+    let now = chrono::Local::now().to_rfc3339();
+    if let Some(input) = input {
+        history.push(ChatMessage {
+            role: "user".to_string(),
+            content: input.to_string(),
+            timestamp: Some(now.clone()),
+            speaker_id: None,
+        });
+    }
+    let reply = ChatMessage {
+        role: "assistant".to_string(),
+        content: answer,
+        timestamp: Some(now),
+        speaker_id: Some(speaker.clone()),
+    };
+    history.push(reply.clone());
+    write_group_history(&state.vault, group_id, &history)?;
+
+    if let Err(e) = relationship::record_turn(&state.vault, &speaker, &group.persona_id, input.unwrap_or_default(), &reply.content) {
+        eprintln!("⚠️ État de la relation non mis à jour: {}", e);
+    }
+
+    Ok(reply)
+}
+
+async fn next_speaker(
+    state: &AppState,
+    group: &GroupChat,
+    input: Option<&str>,
+    history: &[ChatMessage],
+) -> AppResult<String> {
+    match group.strategy {
+        TurnStrategy::RoundRobin => Ok(round_robin(group, history)),
+        TurnStrategy::Manual => Err(AppError::invalid_field("speaker_id", t(Msg::GroupSpeakerRequired))),
+        TurnStrategy::Addressed => {
+            let characters = file_utils::read_characters_file()?;
+            Ok(input
+                .and_then(|text| addressed(group, &characters, text))
+                .unwrap_or_else(|| round_robin(group, history)))
+        }
+        TurnStrategy::LlmChosen => match chosen_by_model(state, group, input, history).await {
+            Ok(Some(id)) if group.character_ids.contains(&id) => Ok(id),
+            Ok(_) => Ok(round_robin(group, history)),
+            Err(e) => {
+                eprintln!("⚠️ Choix du prochain personnage impossible, tour suivant: {}", e);
+                Ok(round_robin(group, history))
+            }
+        },
+    }
+}
+
+/// Le membre qui suit le dernier à avoir parlé.
+fn round_robin(group: &GroupChat, history: &[ChatMessage]) -> String {
+    let last = history
+        .iter()
+        .rev()
+        .find_map(|m| m.speaker_id.as_ref())
+        .and_then(|id| group.character_ids.iter().position(|c| c == id));
+
+    let next = last.map(|i| (i + 1) % group.character_ids.len()).unwrap_or(0);
+    group.character_ids[next].clone()
+}
+
+/// Le membre dont le nom apparaît en premier dans le message.
+fn addressed(group: &GroupChat, characters: &[Character], text: &str) -> Option<String> {
+    let text = text.to_lowercase();
+    characters
+        .iter()
+        .filter(|c| group.character_ids.contains(&c.id) && !c.name.trim().is_empty())
+        .filter_map(|c| text.find(&c.name.to_lowercase()).map(|at| (at, c.id.clone())))
+        .min_by_key(|(at, _)| *at)
+        .map(|(_, id)| id)
+}
+
+async fn chosen_by_model(
+    state: &AppState,
+    group: &GroupChat,
+    input: Option<&str>,
+    history: &[ChatMessage],
+) -> AppResult<Option<String>> {
+    let settings = state.settings.get();
+    let request = NextSpeakerRequest {
+        character_ids: group.character_ids.clone(),
+        question: input.unwrap_or_default().to_string(),
+        memory: history.to_vec(),
+        model: settings.model,
+        language: settings.locale,
+    };
+
+    let url = format!("{}/next-speaker", state.supervisor.base_url(ServiceKind::Go));
+    let response = state
+        .http
+        .post_json::<NextSpeakerRequest, NextSpeakerResponse>(ServiceKind::Go, &url, &request, Retry::Idempotent)
+        .await?;
+    Ok(response.speaker_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(members: &[&str]) -> GroupChat {
+        GroupChat {
+            id: "group_1".to_string(),
+            name: "Taverne".to_string(),
+            character_ids: members.iter().map(|m| m.to_string()).collect(),
+            persona_id: "bob_1".to_string(),
+            strategy: Default::default(),
+        }
+    }
+
+    fn message(speaker_id: Option<&str>) -> ChatMessage {
+        ChatMessage {
+            role: "assistant".to_string(),
+            content: String::new(),
+            timestamp: None,
+            speaker_id: speaker_id.map(str::to_string),
+        }
+    }
+
+    fn character(id: &str, name: &str) -> Character {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "tagline": "",
+            "description": "",
+            "greeting": "",
+            "definition": "",
+            "tags": {},
+            "img": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn round_robin_follows_the_last_speaker() {
+        let group = group(&["ann_1", "ben_2", "cid_3"]);
+
+        assert_eq!(round_robin(&group, &[]), "ann_1");
+        assert_eq!(round_robin(&group, &[message(Some("ann_1")), message(None)]), "ben_2");
+        assert_eq!(round_robin(&group, &[message(Some("ben_2")), message(Some("cid_3"))]), "ann_1");
+        assert_eq!(round_robin(&group, &[message(Some("left_9"))]), "ann_1");
+    }
+
+    #[test]
+    fn addressed_picks_the_first_member_named() {
+        let group = group(&["ann_1", "ben_2"]);
+        let characters = vec![character("ann_1", "Ann"), character("ben_2", "Ben"), character("zoe_3", "Zoé")];
+
+        assert_eq!(addressed(&group, &characters, "BEN, puis ann : vous venez ?"), Some("ben_2".to_string()));
+        assert_eq!(addressed(&group, &characters, "Zoé, tu es là ?"), None);
+        assert_eq!(addressed(&group, &characters, "Bonsoir à tous"), None);
+    }
+
+    #[test]
+    fn group_ids_cannot_escape_the_history_folder() {
+        let vault = Vault::new();
+
+        for id in ["../personas", "a/b", "..", ".hidden", "C:evil", ""] {
+            assert!(read_group_history(&vault, id).is_err(), "{id:?} accepté");
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::http::{HttpClient, REQUEST_ID_HEADER};
+use crate::i18n::{tf, Msg};
+use crate::services::base_url;
+use crate::settings::SettingsStore;
+use crate::supervisor::Supervisor;
+use crate::types::{HealthReport, HealthState, ServiceHealth, ServiceKind, ServiceStatus};
+use serde_json::Value;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Émis pour un service dont l'état de santé vient de changer.
+pub const HEALTH_CHANGED_EVENT: &str = "service-health-changed";
+
+/// Vérifie régulièrement la santé des services et prévient l'interface des changements,
+/// pour qu'elle n'ait pas à interroger elle-même les services.
+pub struct HealthMonitor {
+    supervisor: Arc<Supervisor>,
+    settings: Arc<SettingsStore>,
+    http: Arc<HttpClient>,
+    latest: Mutex<Option<HealthReport>>,
+    app: OnceLock<AppHandle>,
+}
+
+impl HealthMonitor {
+    pub fn new(supervisor: Arc<Supervisor>, settings: Arc<SettingsStore>, http: Arc<HttpClient>) -> Self {
+        Self {
+            supervisor,
+            settings,
+            http,
+            latest: Mutex::new(None),
+            app: OnceLock::new(),
+        }
+    }
+
+    pub fn attach(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    pub fn spawn(self: &Arc<Self>) {
+        let monitor = Arc::clone(self);
+        thread::spawn(move || loop {
+            monitor.refresh();
+            thread::sleep(Duration::from_secs(monitor.settings.get().health_interval_secs));
+        });
+    }
+
+    /// Interroge tous les services, enregistre le rapport et émet un événement pour chaque
+    /// service dont l'état a changé depuis la vérification précédente. Bloquant.
+    pub fn refresh(&self) -> HealthReport {
+        let services: Vec<ServiceHealth> = self
+            .supervisor
+            .status()
+            .iter()
+            .map(|status| self.check(status))
+            .collect();
+
+        let report = HealthReport {
+            healthy: services.iter().all(|s| s.state == HealthState::Healthy),
+            services,
+        };
+
+        let changed: Vec<ServiceHealth> = {
+            let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+            let changed = report
+                .services
+                .iter()
+                .filter(|current| {
+                    let previous = latest
+                        .as_ref()
+                        .and_then(|r| r.services.iter().find(|p| p.service == current.service));
+                    previous.map(|p| p.state != current.state).unwrap_or(true)
+                })
+                .cloned()
+                .collect();
+            *latest = Some(report.clone());
+            changed
+        };
+
+        if let Some(app) = self.app.get() {
+            for health in changed {
+                let _ = app.emit(HEALTH_CHANGED_EVENT, health);
+            }
+        }
+
+        report
+    }
+
+    fn check(&self, status: &ServiceStatus) -> ServiceHealth {
+        let url = base_url(status.port);
+        let started = Instant::now();
+
+        let request = self
+            .http
+            .probe()
+            .get(health_url(status.service, &url))
+            .header(REQUEST_ID_HEADER, self.http.request_id());
+
+        let (state, body, error) = match request.send() {
+            Ok(response) if response.status().is_success() => {
+                (HealthState::Healthy, response.json::<Value>().ok(), None)
+            }
+            Ok(response) => (
+                HealthState::Unhealthy,
+                None,
+                Some(tf(Msg::HealthBadStatus, &[&status.service.label(), &response.status()])),
+            ),
+            Err(e) => (
+                HealthState::Unreachable,
+                None,
+                Some(tf(Msg::HealthUnreachable, &[&status.service.label(), &e])),
+            ),
+        };
+        let latency_ms = (state != HealthState::Unreachable).then(|| started.elapsed().as_millis() as u64);
+
+        let version = body.as_ref().and_then(|b| b["version"].as_str()).map(str::to_string);
+        let model = match status.service {
+            ServiceKind::Ollama if state == HealthState::Healthy => self.ollama_loaded_model(&url),
+            ServiceKind::Python => body.as_ref().and_then(|b| b["model"].as_str()).map(str::to_string),
+            _ => None,
+        };
+
+        ServiceHealth {
+            service: status.service,
+            state,
+            latency_ms,
+            version,
+            model,
+            last_error: error.or_else(|| status.last_error.clone()),
+            uptime_secs: status.uptime_secs,
+            checked_at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    /// Premier modèle chargé en mémoire par Ollama, s'il y en a un.
+    fn ollama_loaded_model(&self, url: &str) -> Option<String> {
+        let body: Value = self.http.probe().get(format!("{}/api/ps", url)).send().ok()?.json().ok()?;
+        body["models"].get(0)?["name"].as_str().map(str::to_string)
+    }
+}
+
+/// Ollama expose sa version sur `/api/version` ; nos services sur `/health`.
+fn health_url(kind: ServiceKind, url: &str) -> String {
+    match kind {
+        ServiceKind::Ollama => format!("{}/api/version", url),
+        ServiceKind::Python | ServiceKind::Go => format!("{}/health", url),
+    }
+}
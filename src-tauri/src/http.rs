@@ -0,0 +1,244 @@
+use crate::error::{AppError, AppResult};
+use crate::i18n::{t, tf, Msg};
+use crate::types::ServiceKind;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Une génération peut prendre jusqu'à 45 s côté Python, plus le trajet par l'API Go.
+/// Ne s'applique pas aux flux, dont la durée totale n'est pas bornée.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(90);
+/// Silence maximal entre deux lectures, y compris pendant un flux : c'est lui qui détecte
+/// un service figé en cours de génération. Large, car Ollama peut charger le modèle avant
+/// d'émettre le premier jeton.
+pub const READ_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Sondes de santé et de démarrage, qui doivent répondre vite.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub const MAX_ATTEMPTS: u32 = 3;
+pub const BACKOFF_BASE: Duration = Duration::from_millis(250);
+
+/// Nombre d'échecs consécutifs au-delà duquel on cesse d'appeler un service.
+pub const BREAKER_THRESHOLD: u32 = 5;
+pub const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// En-tête transmis aux services annexes, qui le reprennent dans leurs journaux.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Ce qu'on peut rejouer sans risque en cas d'échec.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retry {
+    /// Requête rejouable : erreurs de connexion, délais dépassés et 502/503/504.
+    Idempotent,
+    /// Requête avec effet de bord (`/ask` enregistre l'historique) : seulement si elle
+    /// n'a pas pu atteindre le service.
+    ConnectOnly,
+}
+
+#[derive(Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Client HTTP partagé par toutes les commandes : un seul pool de connexions, des délais
+/// bornés, des nouvelles tentatives et un disjoncteur par service.
+pub struct HttpClient {
+    client: reqwest::Client,
+    probe: reqwest::blocking::Client,
+    breakers: Mutex<HashMap<ServiceKind, Breaker>>,
+    next_id: AtomicU64,
+    session: u64,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(READ_IDLE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let probe = reqwest::blocking::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            probe,
+            breakers: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            session: chrono::Local::now().timestamp_millis() as u64 & 0xffff_ffff,
+        }
+    }
+
+    /// Client bloquant pour les sondes, utilisé hors du runtime async.
+    pub fn probe(&self) -> &reqwest::blocking::Client {
+        &self.probe
+    }
+
+    pub fn request_id(&self) -> String {
+        format!("{:08x}-{:06x}", self.session, self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub async fn post_json<T, R>(&self, service: ServiceKind, url: &str, payload: &T, retry: Retry) -> AppResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .send(service, retry, || self.client.post(url).timeout(REQUEST_TIMEOUT).json(payload))
+            .await?;
+        response.json::<R>().await.map_err(|e| AppError::parse(url, e))
+    }
+
+    pub async fn post<T: Serialize>(&self, service: ServiceKind, url: &str, payload: &T, retry: Retry) -> AppResult<()> {
+        self.send(service, retry, || self.client.post(url).timeout(REQUEST_TIMEOUT).json(payload))
+            .await?;
+        Ok(())
+    }
+
+    /// Comme `post`, mais le corps de la réponse est passé ligne par ligne à `on_line` au fil
+    /// de sa réception (flux NDJSON). Seul `READ_IDLE_TIMEOUT` s'applique : une réponse longue
+    /// n'est pas coupée tant que des lignes arrivent.
+    pub async fn post_lines<T: Serialize>(
+        &self,
+        service: ServiceKind,
+        url: &str,
+        payload: &T,
+        retry: Retry,
+        mut on_line: impl FnMut(&str),
+    ) -> AppResult<()> {
+        let mut response = self.send(service, retry, || self.client.post(url).json(payload)).await?;
+
+        let mut pending = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if !line.trim().is_empty() {
+                    on_line(line.trim());
+                }
+            }
+        }
+
+        let rest = String::from_utf8_lossy(&pending);
+        if !rest.trim().is_empty() {
+            on_line(rest.trim());
+        }
+        Ok(())
+    }
+
+    /// Envoie la requête construite par `build`, en la rejouant avec un délai exponentiel
+    /// tant que `retry` le permet. Les réponses non 2xx deviennent des `AppError::Backend`.
+    async fn send(&self, service: ServiceKind, retry: Retry, build: impl Fn() -> RequestBuilder) -> AppResult<Response> {
+        self.check_breaker(service)?;
+
+        let request_id = self.request_id();
+        let mut attempt = 1;
+
+        let result = loop {
+            let result = build().header(REQUEST_ID_HEADER, &request_id).send().await;
+
+            let retryable = match &result {
+                Ok(response) => retry == Retry::Idempotent && is_transient(response.status()),
+                Err(e) => e.is_connect() || (retry == Retry::Idempotent && e.is_timeout()),
+            };
+            if !retryable || attempt >= MAX_ATTEMPTS {
+                break result;
+            }
+
+            let delay = BACKOFF_BASE * 2u32.pow(attempt - 1);
+            eprintln!(
+                "⚠️ [{}] {} : tentative {}/{} échouée, nouvel essai dans {} ms",
+                request_id,
+                service.label(),
+                attempt,
+                MAX_ATTEMPTS,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
+        let failed = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(_) => true,
+        };
+        self.record(service, !failed);
+
+        match result {
+            Ok(response) if response.status().is_success() => Ok(response),
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let body = response.text().await.unwrap_or_else(|_| t(Msg::UnreadableResponseBody).to_string());
+                eprintln!("❌ [{}] {} a répondu {}: {}", request_id, service.label(), status, body);
+                Err(AppError::backend(service.label(), Some(status), body))
+            }
+            Err(e) => {
+                eprintln!("❌ [{}] {} injoignable: {}", request_id, service.label(), e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Refuse l'appel tant que le disjoncteur est ouvert ; une fois le délai écoulé, une
+    /// requête d'essai passe et un nouvel échec le rouvre aussitôt.
+    fn check_breaker(&self, service: ServiceKind) -> AppResult<()> {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = breakers.entry(service).or_default();
+
+        match breaker.open_until {
+            Some(until) if until > Instant::now() => {
+                let remaining = (until - Instant::now()).as_secs() + 1;
+                Err(AppError::backend(
+                    service.label(),
+                    None,
+                    tf(Msg::CircuitOpen, &[&service.label(), &remaining]),
+                ))
+            }
+            Some(_) => {
+                breaker.open_until = None;
+                breaker.failures = BREAKER_THRESHOLD - 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn record(&self, service: ServiceKind, success: bool) {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = breakers.entry(service).or_default();
+
+        if success {
+            breaker.failures = 0;
+            return;
+        }
+
+        breaker.failures += 1;
+        if breaker.failures >= BREAKER_THRESHOLD {
+            eprintln!("⚠️ {} : {} échecs consécutifs, appels suspendus", service.label(), breaker.failures);
+            breaker.open_until = Some(Instant::now() + BREAKER_COOLDOWN);
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
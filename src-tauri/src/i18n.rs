@@ -0,0 +1,421 @@
+use crate::types::Locale;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Langue des messages renvoyés à l'interface, suivie depuis les réglages.
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(Locale::Fr as u8);
+
+pub fn set_locale(locale: Locale) {
+    CURRENT_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        x if x == Locale::En as u8 => Locale::En,
+        _ => Locale::Fr,
+    }
+}
+
+/// Clés du catalogue. Les `{}` des gabarits sont remplacés dans l'ordre par `tf`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Msg {
+    ServicesAlreadyStarted,
+    ServicesStarted,
+    ConversationReset,
+    CharacterSaved,
+    PersonaSaved,
+    UnreadableResponseBody,
+    ChatUnavailable,
+    UnknownService,
+    CircuitOpen,
+    StreamInterrupted,
+    ApiUnauthorized,
+    ApiNoUserMessage,
+    ApiNoPersona,
+    GroupTooSmall,
+    InvalidId,
+    GroupSpeakerRequired,
+    GroupSpeakerNotMember,
+    Narrator,
+    PresetNameRequired,
+    PresetBuiltin,
+    ValueOutOfRange,
+    PresetCasual,
+    PresetDramatic,
+    PresetIntimate,
+    PresetAction,
+    PersonalityBalanced,
+    PersonalityCompanion,
+    PersonalityMentor,
+    PersonalityTrickster,
+    PersonalityRival,
+    PersonalityStoryteller,
+    DraftConceptRequired,
+    DraftFieldMissing,
+    DraftMalformed,
+    GreetingOutOfRange,
+    NoDefaultPersona,
+    PassphrasePrompt,
+    ServicesUnhealthy,
+
+    EntityCharacter,
+    EntityPersona,
+    EntityChat,
+    EntitySnapshot,
+    EntityFile,
+    EntityGroup,
+    EntityPreset,
+    EntityCharacterPreset,
+    NotFound,
+    IoRead,
+    IoWrite,
+    IoCreateDir,
+    IoDelete,
+    ParseFailed,
+    BackendFailed,
+    BackendFailedWithStatus,
+    TimedOut,
+    Cancelled,
+    HttpRequest,
+    ServiceStartup,
+
+    ExecutableNotFound,
+    SpawnFailed,
+    PortUnavailable,
+    NotReady,
+    ExitedDuringStartup,
+    Crashed,
+    AdoptedInstanceGone,
+    HintInstallOllama,
+    HintInstallPython,
+    HintInstallGo,
+    HintCheckExecutable,
+    HintFreePort,
+    HintPullModel,
+    HintSlowService,
+    HintPythonDependencies,
+    HintGoBuild,
+    HintCheckOutput,
+
+    ServiceStopped,
+    HintStartService,
+    AdoptedCannotRestart,
+    PortTakenRelocated,
+    LogInstanceAdopted,
+    LogUnexpectedExit,
+    LogProcessLaunched,
+    PortTakenNoFallback,
+    HealthBadStatus,
+    HealthUnreachable,
+
+    ModelRequired,
+    PortsOutOfRange,
+    PortsNotDistinct,
+    HealthIntervalRange,
+    MaxRestartsRange,
+    SnapshotIntervalRange,
+    RetentionEmpty,
+    ApiTokenRequired,
+
+    VaultUnavailable,
+    VaultNotEnabled,
+    VaultAlreadyEnabled,
+    VaultLocked,
+    PassphraseTooShort,
+    PassphraseIncorrect,
+    KeyDerivationFailed,
+    InvalidKdfParams,
+    InvalidSealedFile,
+    InvalidVaultHeader,
+    EncryptionFailed,
+    DecryptionFailed,
+
+    EmptyImage,
+    ImageTooLarge,
+    UnknownImageFormat,
+    UnsupportedImageFormat,
+    InvalidImage,
+
+    ArchiveEntryNotAllowed,
+    UnsupportedArchiveVersion,
+    ManifestMismatch,
+    ChecksumMismatch,
+}
+
+pub fn t(msg: Msg) -> &'static str {
+    match locale() {
+        Locale::Fr => fr(msg),
+        Locale::En => en(msg),
+    }
+}
+
+/// Gabarit `msg` dont chaque `{}` est remplacé, dans l'ordre, par un argument.
+pub fn tf(msg: Msg, args: &[&dyn Display]) -> String {
+    let mut parts = t(msg).split("{}");
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+fn fr(msg: Msg) -> &'static str {
+    match msg {
+        Msg::ServicesAlreadyStarted => "Services déjà démarrés",
+        Msg::ServicesStarted => "Services démarrés avec succès",
+        Msg::ConversationReset => "Conversation réinitialisée",
+        Msg::CharacterSaved => "Personnage sauvegardé",
+        Msg::PersonaSaved => "Persona sauvegardée",
+        Msg::UnreadableResponseBody => "Impossible de lire le corps de la réponse",
+        Msg::ChatUnavailable => "Chat indisponible en mode dégradé{}",
+        Msg::UnknownService => "Service inconnu du superviseur",
+        Msg::CircuitOpen => "{} a échoué plusieurs fois de suite, nouvel essai possible dans {} s",
+        Msg::StreamInterrupted => "La réponse a été interrompue avant la fin",
+        Msg::ApiUnauthorized => "Jeton d'accès manquant ou invalide",
+        Msg::ApiNoUserMessage => "La requête ne contient aucun message de l'utilisateur",
+        Msg::ApiNoPersona => "Aucune persona : précisez `user` ou choisissez une persona par défaut",
+        Msg::GroupTooSmall => "Un groupe doit réunir au moins deux personnages différents",
+        Msg::InvalidId => "Identifiant invalide : {}",
+        Msg::GroupSpeakerRequired => "Choisissez le personnage qui doit répondre",
+        Msg::GroupSpeakerNotMember => "{} ne fait pas partie de ce groupe",
+        Msg::Narrator => "Narrateur",
+        Msg::PresetNameRequired => "Le préréglage doit avoir un nom",
+        Msg::PresetBuiltin => "Le préréglage « {} » est fourni avec l'application et ne peut pas être modifié",
+        Msg::ValueOutOfRange => "{} doit être compris entre {} et {}",
+        Msg::PresetCasual => "Décontractée",
+        Msg::PresetDramatic => "Dramatique",
+        Msg::PresetIntimate => "Intime",
+        Msg::PresetAction => "Action",
+        Msg::PersonalityBalanced => "Équilibré",
+        Msg::PersonalityCompanion => "Compagnon chaleureux",
+        Msg::PersonalityMentor => "Mentor stoïque",
+        Msg::PersonalityTrickster => "Farceur",
+        Msg::PersonalityRival => "Rival ombrageux",
+        Msg::PersonalityStoryteller => "Conteur bavard",
+        Msg::DraftConceptRequired => "Décrivez le personnage en quelques mots",
+        Msg::DraftFieldMissing => "le champ « {} » est vide",
+        Msg::DraftMalformed => "Le modèle n'a pas produit de personnage valide après {} essais : {}",
+        Msg::GreetingOutOfRange => "Ce personnage n'a pas de salutation n°{}",
+        Msg::NoDefaultPersona => "Ce personnage n'a pas de persona par défaut : précisez-en une",
+        Msg::PassphrasePrompt => "🔐 Phrase secrète du coffre : ",
+        Msg::ServicesUnhealthy => "au moins un service ne répond pas",
+
+        Msg::EntityCharacter => "Personnage",
+        Msg::EntityPersona => "Persona",
+        Msg::EntityChat => "Conversation",
+        Msg::EntitySnapshot => "Snapshot",
+        Msg::EntityFile => "Fichier",
+        Msg::EntityGroup => "Groupe",
+        Msg::EntityPreset => "Préréglage de scène",
+        Msg::EntityCharacterPreset => "Préréglage de personnalité",
+        Msg::NotFound => "{} '{}' non trouvé",
+        Msg::IoRead => "Erreur lecture fichier",
+        Msg::IoWrite => "Erreur écriture fichier",
+        Msg::IoCreateDir => "Erreur création dossier",
+        Msg::IoDelete => "Erreur suppression fichier",
+        Msg::ParseFailed => "Erreur parsing {}: {}",
+        Msg::BackendFailed => "Erreur {}: {}",
+        Msg::BackendFailedWithStatus => "Erreur {} ({}): {}",
+        Msg::TimedOut => "Délai dépassé: {}",
+        Msg::Cancelled => "Opération annulée: {}",
+        Msg::HttpRequest => "requête HTTP",
+        Msg::ServiceStartup => "démarrage de {}",
+
+        Msg::ExecutableNotFound => "{}: commande '{}' introuvable",
+        Msg::SpawnFailed => "Échec du lancement de {}: {}",
+        Msg::PortUnavailable => "{}: le port {} est occupé et aucun port libre n'a été trouvé",
+        Msg::NotReady => "{} ne répond pas après {} s",
+        Msg::ExitedDuringStartup => "{} s'est arrêté pendant le démarrage",
+        Msg::Crashed => "{} s'est arrêté ({})",
+        Msg::AdoptedInstanceGone => "l'instance adoptée ne répond plus",
+        Msg::HintInstallOllama => "Installez Ollama depuis https://ollama.com puis relancez l'application",
+        Msg::HintInstallPython => "Installez Python 3 et vérifiez que la commande python3 est dans le PATH",
+        Msg::HintInstallGo => "Installez Go depuis https://go.dev/dl et vérifiez que la commande go est dans le PATH",
+        Msg::HintCheckExecutable => {
+            "Vérifiez que le programme est exécutable et que le dossier de l'application est accessible"
+        }
+        Msg::HintFreePort => "Fermez le programme qui utilise le port {} puis redémarrez le service",
+        Msg::HintPullModel => {
+            "Vérifiez que le modèle est installé (ollama pull dolphin-mistral) puis redémarrez le service"
+        }
+        Msg::HintSlowService => "Le service met trop de temps à répondre : redémarrez-le depuis les paramètres",
+        Msg::HintPythonDependencies => "Installez les dépendances Python : pip install -r python-llm/requirements.txt",
+        Msg::HintGoBuild => "Vérifiez que le module go-api compile : cd go-api && go build",
+        Msg::HintCheckOutput => "Consultez la sortie du service puis redémarrez-le depuis les paramètres",
+
+        Msg::ServiceStopped => "{} est arrêté",
+        Msg::HintStartService => "Démarrez le service depuis les paramètres",
+        Msg::AdoptedCannotRestart => "{} a été lancé hors de l'application et ne peut pas être redémarré",
+        Msg::PortTakenRelocated => "Le port {} est occupé par un autre programme : {} utilisera le port {}",
+        Msg::LogInstanceAdopted => "Instance existante adoptée sur le port {}",
+        Msg::LogUnexpectedExit => "Arrêt inattendu ({})",
+        Msg::LogProcessLaunched => "Processus {} lancé sur le port {}",
+        Msg::PortTakenNoFallback => {
+            "Le port {} est occupé par un autre programme et aucun port libre n'a été trouvé pour {}"
+        }
+        Msg::HealthBadStatus => "{} répond avec le statut: {}",
+        Msg::HealthUnreachable => "{} non disponible: {}",
+
+        Msg::ModelRequired => "Le modèle ne peut pas être vide",
+        Msg::PortsOutOfRange => "Les ports doivent être compris entre {} et {}",
+        Msg::PortsNotDistinct => "Chaque service doit avoir un port différent",
+        Msg::HealthIntervalRange => "L'intervalle de vérification doit être compris entre 1 et 3600 secondes",
+        Msg::MaxRestartsRange => "Le nombre de redémarrages ne peut pas dépasser {}",
+        Msg::SnapshotIntervalRange => "L'intervalle des snapshots doit être compris entre 1 et {} minutes",
+        Msg::RetentionEmpty => "La politique de rétention doit conserver au moins un snapshot",
+        Msg::ApiTokenRequired => "Un jeton d'accès est requis pour activer le serveur compatible OpenAI",
+
+        Msg::VaultUnavailable => "Coffre inaccessible",
+        Msg::VaultNotEnabled => "Le coffre n'est pas activé",
+        Msg::VaultAlreadyEnabled => "Le coffre est déjà activé",
+        Msg::VaultLocked => "Le coffre est verrouillé",
+        Msg::PassphraseTooShort => "La phrase secrète doit contenir au moins 8 caractères",
+        Msg::PassphraseIncorrect => "Phrase secrète incorrecte",
+        Msg::KeyDerivationFailed => "Erreur dérivation de clé: {}",
+        Msg::InvalidKdfParams => "paramètres Argon2 invalides: {}",
+        Msg::InvalidSealedFile => "fichier chiffré invalide",
+        Msg::InvalidVaultHeader => "en-tête du coffre invalide",
+        Msg::EncryptionFailed => "chiffrement impossible",
+        Msg::DecryptionFailed => "déchiffrement impossible",
+
+        Msg::EmptyImage => "Image vide",
+        Msg::ImageTooLarge => "Image trop lourde (maximum {} Mo)",
+        Msg::UnknownImageFormat => "Format d'image non reconnu",
+        Msg::UnsupportedImageFormat => "Format d'image non supporté: {}",
+        Msg::InvalidImage => "Image invalide: {}",
+
+        Msg::ArchiveEntryNotAllowed => "Entrée d'archive non autorisée: {}",
+        Msg::UnsupportedArchiveVersion => "Version d'archive {} non supportée (maximum {})",
+        Msg::ManifestMismatch => "Le manifeste ne correspond pas au contenu de l'archive",
+        Msg::ChecksumMismatch => "Somme de contrôle invalide pour {}",
+    }
+}
+
+fn en(msg: Msg) -> &'static str {
+    match msg {
+        Msg::ServicesAlreadyStarted => "Services already started",
+        Msg::ServicesStarted => "Services started successfully",
+        Msg::ConversationReset => "Conversation reset",
+        Msg::CharacterSaved => "Character saved",
+        Msg::PersonaSaved => "Persona saved",
+        Msg::UnreadableResponseBody => "Unable to read the response body",
+        Msg::ChatUnavailable => "Chat is unavailable in degraded mode{}",
+        Msg::UnknownService => "Service unknown to the supervisor",
+        Msg::CircuitOpen => "{} failed several times in a row, next attempt possible in {} s",
+        Msg::StreamInterrupted => "The response was interrupted before it finished",
+        Msg::ApiUnauthorized => "Missing or invalid access token",
+        Msg::ApiNoUserMessage => "The request contains no user message",
+        Msg::ApiNoPersona => "No persona: set `user` or choose a default persona",
+        Msg::GroupTooSmall => "A group needs at least two different characters",
+        Msg::InvalidId => "Invalid identifier: {}",
+        Msg::GroupSpeakerRequired => "Choose the character who should reply",
+        Msg::GroupSpeakerNotMember => "{} is not part of this group",
+        Msg::Narrator => "Narrator",
+        Msg::PresetNameRequired => "The preset needs a name",
+        Msg::PresetBuiltin => "The \"{}\" preset ships with the app and cannot be changed",
+        Msg::ValueOutOfRange => "{} must be between {} and {}",
+        Msg::PresetCasual => "Casual",
+        Msg::PresetDramatic => "Dramatic",
+        Msg::PresetIntimate => "Intimate",
+        Msg::PresetAction => "Action",
+        Msg::PersonalityBalanced => "Balanced",
+        Msg::PersonalityCompanion => "Warm companion",
+        Msg::PersonalityMentor => "Stoic mentor",
+        Msg::PersonalityTrickster => "Trickster",
+        Msg::PersonalityRival => "Brooding rival",
+        Msg::PersonalityStoryteller => "Chatty storyteller",
+        Msg::DraftConceptRequired => "Describe the character in a few words",
+        Msg::DraftFieldMissing => "the \"{}\" field is empty",
+        Msg::DraftMalformed => "The model did not produce a valid character after {} attempts: {}",
+        Msg::GreetingOutOfRange => "This character has no greeting #{}",
+        Msg::NoDefaultPersona => "This character has no default persona: specify one",
+        Msg::PassphrasePrompt => "🔐 Vault passphrase: ",
+        Msg::ServicesUnhealthy => "at least one service is not responding",
+
+        Msg::EntityCharacter => "Character",
+        Msg::EntityPersona => "Persona",
+        Msg::EntityChat => "Conversation",
+        Msg::EntitySnapshot => "Snapshot",
+        Msg::EntityFile => "File",
+        Msg::EntityGroup => "Group",
+        Msg::EntityPreset => "Scene preset",
+        Msg::EntityCharacterPreset => "Personality preset",
+        Msg::NotFound => "{} '{}' not found",
+        Msg::IoRead => "Could not read file",
+        Msg::IoWrite => "Could not write file",
+        Msg::IoCreateDir => "Could not create folder",
+        Msg::IoDelete => "Could not delete file",
+        Msg::ParseFailed => "Could not parse {}: {}",
+        Msg::BackendFailed => "{} error: {}",
+        Msg::BackendFailedWithStatus => "{} error ({}): {}",
+        Msg::TimedOut => "Timed out: {}",
+        Msg::Cancelled => "Operation cancelled: {}",
+        Msg::HttpRequest => "HTTP request",
+        Msg::ServiceStartup => "starting {}",
+
+        Msg::ExecutableNotFound => "{}: command '{}' not found",
+        Msg::SpawnFailed => "Could not launch {}: {}",
+        Msg::PortUnavailable => "{}: port {} is taken and no free port was found",
+        Msg::NotReady => "{} is not responding after {} s",
+        Msg::ExitedDuringStartup => "{} stopped during startup",
+        Msg::Crashed => "{} stopped ({})",
+        Msg::AdoptedInstanceGone => "the adopted instance stopped responding",
+        Msg::HintInstallOllama => "Install Ollama from https://ollama.com then restart the application",
+        Msg::HintInstallPython => "Install Python 3 and make sure the python3 command is on the PATH",
+        Msg::HintInstallGo => "Install Go from https://go.dev/dl and make sure the go command is on the PATH",
+        Msg::HintCheckExecutable => "Make sure the program is executable and the application folder is accessible",
+        Msg::HintFreePort => "Close the program using port {} then restart the service",
+        Msg::HintPullModel => "Make sure the model is installed (ollama pull dolphin-mistral) then restart the service",
+        Msg::HintSlowService => "The service is taking too long to respond: restart it from the settings",
+        Msg::HintPythonDependencies => "Install the Python dependencies: pip install -r python-llm/requirements.txt",
+        Msg::HintGoBuild => "Make sure the go-api module builds: cd go-api && go build",
+        Msg::HintCheckOutput => "Check the service output then restart it from the settings",
+
+        Msg::ServiceStopped => "{} is stopped",
+        Msg::HintStartService => "Start the service from the settings",
+        Msg::AdoptedCannotRestart => "{} was started outside the application and cannot be restarted",
+        Msg::PortTakenRelocated => "Port {} is used by another program: {} will use port {}",
+        Msg::LogInstanceAdopted => "Adopted the instance already running on port {}",
+        Msg::LogUnexpectedExit => "Unexpected exit ({})",
+        Msg::LogProcessLaunched => "Process {} launched on port {}",
+        Msg::PortTakenNoFallback => "Port {} is used by another program and no free port was found for {}",
+        Msg::HealthBadStatus => "{} responded with status: {}",
+        Msg::HealthUnreachable => "{} is unavailable: {}",
+
+        Msg::ModelRequired => "The model cannot be empty",
+        Msg::PortsOutOfRange => "Ports must be between {} and {}",
+        Msg::PortsNotDistinct => "Each service must use a different port",
+        Msg::HealthIntervalRange => "The health check interval must be between 1 and 3600 seconds",
+        Msg::MaxRestartsRange => "The number of restarts cannot exceed {}",
+        Msg::SnapshotIntervalRange => "The snapshot interval must be between 1 and {} minutes",
+        Msg::RetentionEmpty => "The retention policy must keep at least one snapshot",
+        Msg::ApiTokenRequired => "An access token is required to enable the OpenAI-compatible server",
+
+        Msg::VaultUnavailable => "Vault unavailable",
+        Msg::VaultNotEnabled => "The vault is not enabled",
+        Msg::VaultAlreadyEnabled => "The vault is already enabled",
+        Msg::VaultLocked => "The vault is locked",
+        Msg::PassphraseTooShort => "The passphrase must be at least 8 characters long",
+        Msg::PassphraseIncorrect => "Incorrect passphrase",
+        Msg::KeyDerivationFailed => "Key derivation failed: {}",
+        Msg::InvalidKdfParams => "invalid Argon2 parameters: {}",
+        Msg::InvalidSealedFile => "invalid encrypted file",
+        Msg::InvalidVaultHeader => "invalid vault header",
+        Msg::EncryptionFailed => "encryption failed",
+        Msg::DecryptionFailed => "decryption failed",
+
+        Msg::EmptyImage => "Empty image",
+        Msg::ImageTooLarge => "Image too large (maximum {} MB)",
+        Msg::UnknownImageFormat => "Unrecognized image format",
+        Msg::UnsupportedImageFormat => "Unsupported image format: {}",
+        Msg::InvalidImage => "Invalid image: {}",
+
+        Msg::ArchiveEntryNotAllowed => "Archive entry not allowed: {}",
+        Msg::UnsupportedArchiveVersion => "Unsupported archive version {} (maximum {})",
+        Msg::ManifestMismatch => "The manifest does not match the archive contents",
+        Msg::ChecksumMismatch => "Invalid checksum for {}",
+    }
+}
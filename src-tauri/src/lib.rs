@@ -1,16 +1,134 @@
+pub mod types;
+pub mod assets;
+pub mod chat;
+pub mod services;
+pub mod settings;
+pub mod file_utils;
+pub mod health;
+pub mod http;
+pub mod i18n;
+pub mod commands;
+pub mod error;
+pub mod group;
+pub mod library;
+pub mod relationship;
+pub mod logs;
+pub mod scene;
+pub mod openai;
+pub mod personality;
+pub mod presets;
+pub mod snapshots;
+pub mod supervisor;
+pub mod transcript;
+pub mod vault;
+pub mod wizard;
+
+use crate::types::AppState;
+use crate::commands::*;
+use std::sync::Arc;
+use std::thread;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-  tauri::Builder::default()
-    .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
-      }
-      Ok(())
-    })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    let app_state = AppState::new();
+    let settings = Arc::clone(&app_state.settings);
+    let supervisor = Arc::clone(&app_state.supervisor);
+    let health = Arc::clone(&app_state.health);
+
+    supervisor.spawn_monitor();
+    snapshots::start_snapshot_service(Arc::clone(&settings));
+    openai::spawn(app_state.clone());
+
+    let startup = Arc::clone(&supervisor);
+
+    tauri::Builder::default()
+        .manage(app_state)
+        .setup(move |app| {
+            // Les services démarrent en arrière-plan : la fenêtre s'affiche sans attendre.
+            settings.attach(app.handle().clone());
+            startup.attach(app.handle().clone());
+            health.attach(app.handle().clone());
+            health.spawn();
+            if !settings.get().auto_start_services {
+                return Ok(());
+            }
+            thread::spawn(move || {
+                if let Err(errors) = startup.start_all() {
+                    for e in errors {
+                        eprintln!("❌ {} — {}", e, e.hint());
+                    }
+                }
+            });
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            start_services,
+            ask_question,
+            reset_conversation,
+            check_services,
+            save_character,
+            load_characters,
+            store_image_asset,
+            gc_image_assets,
+            delete_character,
+            update_character,
+            save_persona,
+            load_personas,
+            delete_persona,
+            update_persona,
+            chat_with_character,
+            load_group_chats,
+            save_group_chat,
+            delete_group_chat,
+            load_group_history,
+            send_group_message,
+            load_relationship_state,
+            reset_relationship_state,
+            load_scene_state,
+            update_scene_state,
+            narrate,
+            list_character_presets,
+            generate_character_draft,
+            start_chat_session,
+            set_default_persona,
+            apply_character_preset,
+            list_scene_presets,
+            save_scene_preset,
+            delete_scene_preset,
+            set_scene_preset,
+            load_character_by_id,
+            load_persona_by_id,
+            check_services_status,
+            load_recent_chats,
+            load_chat_history,
+            delete_chat_history,
+            export_library,
+            import_library,
+            export_chat_transcript,
+            list_snapshots,
+            diff_snapshot,
+            restore_character_from_snapshot,
+            restore_chat_from_snapshot,
+            vault_status,
+            enable_vault,
+            unlock_vault,
+            lock_vault,
+            change_vault_passphrase,
+            start_service,
+            stop_service,
+            restart_service,
+            services_status,
+            system_status,
+            get_service_logs,
+            get_settings,
+            update_settings,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(move |_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                supervisor.stop_all();
+            }
+        });
 }
+
@@ -0,0 +1,1037 @@
+use crate::assets;
+use crate::error::{AppError, AppResult, Entity};
+use crate::i18n::{t, tf, Msg};
+use crate::file_utils::{
+    delete_chat_history, list_chat_histories, read_characters_file, read_chat_history, read_personas_file,
+    sha256_hex, split_history_stem, validate_id, write_characters_file, write_personas_file, ASSETS_PATH, CHARACTERS_FILE_PATH,
+    GROUPS_FILE_PATH, GROUP_HISTORY_DIR, HISTORY_DIR, PERSONAS_FILE_PATH, PERSONA_ASSETS_PATH, ASSET_STORE_PATH,
+    RECENT_CHATS_FILE_PATH, RELATIONSHIPS_DIR, SCENES_DIR, SCENE_PRESETS_FILE_PATH, SETTINGS_FILE_PATH,
+};
+use crate::group;
+use crate::presets;
+use crate::scene::GROUP_SCENE_PREFIX;
+use crate::types::{
+    Character, ChatMessage, ConflictStrategy, GroupChat, ImportMode, ImportOptions, ImportReport, LibraryManifest,
+    ManifestEntry, Persona, RelationshipState, RenamedEntry, ScenePreset, SceneState,
+};
+use crate::vault::{encrypted_path, Vault, ENCRYPTED_EXTENSION};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+pub const LIBRARY_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CHARACTERS_ENTRY: &str = "data/characters.json";
+const PERSONAS_ENTRY: &str = "data/personas.json";
+const RECENT_CHATS_ENTRY: &str = "data/recent_chats.json";
+const SETTINGS_ENTRY: &str = "data/settings.json";
+const GROUPS_ENTRY: &str = "data/groups.json";
+const SCENE_PRESETS_ENTRY: &str = "data/scene_presets.json";
+const HISTORY_PREFIX: &str = "data/history/";
+const GROUP_HISTORY_PREFIX: &str = "data/history/groups/";
+const RELATIONSHIPS_PREFIX: &str = "data/history/relationships/";
+const SCENES_PREFIX: &str = "data/scenes/";
+const CHARACTER_ASSETS_PREFIX: &str = "assets/characters/";
+const PERSONA_ASSETS_PREFIX: &str = "assets/personas/";
+const STORE_ASSETS_PREFIX: &str = "assets/store/";
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Dossiers protégés par le coffre, exportés déchiffrés : préfixe dans l'archive → dossier.
+const SEALED_DIRS: [(&str, &str); 3] = [
+    (GROUP_HISTORY_PREFIX, GROUP_HISTORY_DIR),
+    (RELATIONSHIPS_PREFIX, RELATIONSHIPS_DIR),
+    (SCENES_PREFIX, SCENES_DIR),
+];
+
+type ArchiveFiles = BTreeMap<String, Vec<u8>>;
+
+/// Entités lues dans l'archive.
+struct Library {
+    characters: Vec<Character>,
+    personas: Vec<Persona>,
+    groups: Vec<GroupChat>,
+    scene_presets: Vec<ScenePreset>,
+}
+
+/// Ce qu'un import a besoin de savoir sur une entité de la bibliothèque.
+trait LibraryEntity {
+    fn id(&self) -> &str;
+    fn set_id(&mut self, id: String);
+    fn img_mut(&mut self) -> Option<&mut String> {
+        None
+    }
+}
+
+impl LibraryEntity for Character {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn img_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.img)
+    }
+}
+
+impl LibraryEntity for Persona {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn img_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.img)
+    }
+}
+
+impl LibraryEntity for GroupChat {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+}
+
+impl LibraryEntity for ScenePreset {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+}
+
+/// Écrit toute la bibliothèque (données, historiques, groupes, scènes, relations, images,
+/// réglages) dans une archive zip
+/// accompagnée d'un manifeste versionné contenant la somme SHA-256 de chaque fichier.
+/// Les fichiers protégés par le coffre sont exportés déchiffrés.
+pub fn export_library(vault: &Vault, dest: &Path) -> AppResult<LibraryManifest> {
+    let mut files = ArchiveFiles::new();
+
+    files.insert(
+        CHARACTERS_ENTRY.to_string(),
+        fs::read(CHARACTERS_FILE_PATH).map_err(|e| AppError::read(CHARACTERS_FILE_PATH, e))?,
+    );
+    files.insert(
+        PERSONAS_ENTRY.to_string(),
+        vault
+            .read(Path::new(PERSONAS_FILE_PATH))?
+            .ok_or_else(|| AppError::not_found(Entity::File, PERSONAS_FILE_PATH))?,
+    );
+
+    // Le jeton de l'API compatible OpenAI ne quitte pas la machine.
+    if Path::new(SETTINGS_FILE_PATH).exists() {
+        let data = fs::read(SETTINGS_FILE_PATH).map_err(|e| AppError::read(SETTINGS_FILE_PATH, e))?;
+        files.insert(SETTINGS_ENTRY.to_string(), with_api_token(&data, "")?);
+    }
+
+    for (entry, path) in [
+        (RECENT_CHATS_ENTRY, RECENT_CHATS_FILE_PATH),
+        (GROUPS_ENTRY, GROUPS_FILE_PATH),
+        (SCENE_PRESETS_ENTRY, SCENE_PRESETS_FILE_PATH),
+    ] {
+        if Path::new(path).exists() {
+            files.insert(
+                entry.to_string(),
+                fs::read(path).map_err(|e| AppError::read(path, e))?,
+            );
+        }
+    }
+
+    for (character_id, persona_id) in list_chat_histories() {
+        let messages = read_chat_history(vault, &character_id, &persona_id)?;
+        let entry = format!("{}{}_{}.json", HISTORY_PREFIX, character_id, persona_id);
+        let data = serde_json::to_vec_pretty(&messages).map_err(|e| AppError::parse(entry.as_str(), e))?;
+        files.insert(entry, data);
+    }
+
+    for (prefix, dir) in SEALED_DIRS {
+        collect_sealed_dir(vault, &mut files, prefix, Path::new(dir))?;
+    }
+
+    collect_dir(&mut files, CHARACTER_ASSETS_PREFIX, Path::new(ASSETS_PATH))?;
+    collect_dir(&mut files, PERSONA_ASSETS_PREFIX, Path::new(PERSONA_ASSETS_PATH))?;
+    collect_dir(&mut files, STORE_ASSETS_PREFIX, Path::new(ASSET_STORE_PATH))?;
+
+    let manifest = LibraryManifest {
+        format_version: LIBRARY_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        files: files
+            .iter()
+            .map(|(path, data)| ManifestEntry {
+                path: path.clone(),
+                sha256: sha256_hex(data),
+                size: data.len() as u64,
+            })
+            .collect(),
+    };
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| AppError::parse(MANIFEST_ENTRY, e))?;
+
+    let file = fs::File::create(dest).map_err(|e| AppError::write(dest, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for (path, data) in files.iter().chain(std::iter::once((&MANIFEST_ENTRY.to_string(), &manifest_json))) {
+        zip.start_file(path.as_str(), options)
+            .map_err(|e| AppError::write(dest, e))?;
+        zip.write_all(data)
+            .map_err(|e| AppError::write(dest, e))?;
+    }
+
+    zip.finish().map_err(|e| AppError::write(dest, e))?;
+
+    Ok(manifest)
+}
+
+/// Restaure une archive produite par `export_library`. Le contenu est entièrement vérifié
+/// (manifeste, sommes de contrôle, JSON) avant que le moindre fichier local ne soit modifié.
+pub fn import_library(vault: &Vault, source: &Path, options: &ImportOptions) -> AppResult<ImportReport> {
+    let files = read_archive(source)?;
+
+    // Les archives antérieures aux groupes et aux préréglages de scène n'ont pas ces fichiers.
+    let library = Library {
+        characters: parse_entry(&files, CHARACTERS_ENTRY)?,
+        personas: parse_entry(&files, PERSONAS_ENTRY)?,
+        groups: parse_optional_entry(&files, GROUPS_ENTRY)?.unwrap_or_default(),
+        scene_presets: parse_optional_entry(&files, SCENE_PRESETS_ENTRY)?.unwrap_or_default(),
+    };
+    check_ids(&library)?;
+
+    for (path, data) in &files {
+        if history_file_name(path).is_some() || path.starts_with(GROUP_HISTORY_PREFIX) {
+            check_entry::<Vec<ChatMessage>>(path, data)?;
+        } else if path.starts_with(RELATIONSHIPS_PREFIX) {
+            check_entry::<RelationshipState>(path, data)?;
+        } else if path.starts_with(SCENES_PREFIX) {
+            check_entry::<SceneState>(path, data)?;
+        }
+    }
+
+    match options.mode {
+        ImportMode::Replace => replace_library(vault, &files, library),
+        ImportMode::Merge => merge_library(vault, &files, library, options.on_conflict),
+    }
+}
+
+fn replace_library(vault: &Vault, files: &ArchiveFiles, mut library: Library) -> AppResult<ImportReport> {
+    let mut report = ImportReport {
+        characters_imported: library.characters.len(),
+        personas_imported: library.personas.len(),
+        groups_imported: library.groups.len(),
+        ..Default::default()
+    };
+
+    for (character_id, persona_id) in list_chat_histories() {
+        delete_chat_history(vault, &character_id, &persona_id)?;
+    }
+    for (_, dir) in SEALED_DIRS {
+        clear_sealed_dir(vault, Path::new(dir))?;
+    }
+
+    let mut store_paths = HashMap::new();
+    for (path, data) in files {
+        if let Some(file_name) = history_file_name(path) {
+            write_sealed_file(vault, HISTORY_DIR, file_name, data)?;
+            report.histories_imported += 1;
+        } else if let Some(file_name) = path.strip_prefix(STORE_ASSETS_PREFIX) {
+            if let Some(id) = import_store_image(file_name, data, &mut report)? {
+                store_paths.insert(format!("/{}", path), id);
+            }
+        } else if let Some((dir, file_name)) = sealed_destination(path) {
+            write_sealed_file(vault, dir, file_name, data)?;
+            if dir == GROUP_HISTORY_DIR {
+                report.histories_imported += 1;
+            }
+        } else if let Some((dir, file_name)) = asset_destination(path) {
+            write_file(&dir.join(file_name), data)?;
+            report.assets_imported += 1;
+        }
+    }
+
+    library.characters.iter_mut().for_each(|c| relink_image(c, &store_paths));
+    library.personas.iter_mut().for_each(|p| relink_image(p, &store_paths));
+
+    if let Some(data) = files.get(RECENT_CHATS_ENTRY) {
+        write_file(Path::new(RECENT_CHATS_FILE_PATH), data)?;
+    }
+    // L'archive ne porte pas de jeton d'API : celui de cette machine est conservé.
+    if let Some(data) = files.get(SETTINGS_ENTRY) {
+        write_file(Path::new(SETTINGS_FILE_PATH), &with_api_token(data, &local_api_token())?)?;
+    }
+
+    // Les groupes désignent les personnages remplacés : ceux d'ici ne sont pas conservés.
+    // Les préréglages de scène, indépendants, le sont si l'archive n'en contient pas.
+    group::write_groups_file(&library.groups)?;
+    if files.contains_key(SCENE_PRESETS_ENTRY) {
+        presets::write_user_presets(&library.scene_presets)?;
+    }
+
+    let values: Vec<Value> = library.characters.into_iter().map(|c| json!(c)).collect();
+    write_characters_file(&values)?;
+    write_personas_file(vault, &library.personas)?;
+
+    Ok(report)
+}
+
+fn merge_library(
+    vault: &Vault,
+    files: &ArchiveFiles,
+    library: Library,
+    strategy: ConflictStrategy,
+) -> AppResult<ImportReport> {
+    let mut report = ImportReport::default();
+
+    let mut existing_characters = if Path::new(CHARACTERS_FILE_PATH).exists() {
+        read_characters_file()?
+    } else {
+        vec![]
+    };
+    let mut existing_personas = if Path::new(PERSONAS_FILE_PATH).exists() || encrypted_path(Path::new(PERSONAS_FILE_PATH)).exists() {
+        read_personas_file(vault)?
+    } else {
+        vec![]
+    };
+    let mut existing_groups = group::read_groups_file()?;
+    let mut existing_presets = presets::read_user_presets()?;
+
+    let asset_paths = merge_assets(files, strategy, &mut report)?;
+    let archive_character_ids: Vec<String> = library.characters.iter().map(|c| c.id.clone()).collect();
+
+    let (character_ids, imported) = merge_entities(
+        &mut existing_characters,
+        library.characters,
+        &asset_paths,
+        strategy,
+        "character",
+        &mut report,
+    );
+    report.characters_imported = imported;
+
+    let (persona_ids, imported) = merge_entities(
+        &mut existing_personas,
+        library.personas,
+        &asset_paths,
+        strategy,
+        "persona",
+        &mut report,
+    );
+    report.personas_imported = imported;
+
+    let (preset_ids, _) = merge_entities(
+        &mut existing_presets,
+        library.scene_presets,
+        &asset_paths,
+        strategy,
+        "scene_preset",
+        &mut report,
+    );
+
+    let groups = remap_groups(library.groups, &character_ids, &persona_ids, &mut report);
+    let (group_ids, imported) =
+        merge_entities(&mut existing_groups, groups, &asset_paths, strategy, "group", &mut report);
+    report.groups_imported = imported;
+
+    for (path, data) in files {
+        let destination = if let Some(name) = history_file_name(path) {
+            remap_session_file(name, &archive_character_ids, &character_ids, &persona_ids)
+                .map(|file_name| (HISTORY_DIR, file_name, data.clone()))
+        } else if let Some(name) = path.strip_prefix(GROUP_HISTORY_PREFIX) {
+            name.strip_suffix(".json")
+                .and_then(|group_id| group_ids.get(group_id))
+                .map(|group_id| (GROUP_HISTORY_DIR, format!("{}.json", group_id), data.clone()))
+        } else if let Some(name) = path.strip_prefix(RELATIONSHIPS_PREFIX) {
+            remap_session_file(name, &archive_character_ids, &character_ids, &persona_ids)
+                .map(|file_name| (RELATIONSHIPS_DIR, file_name, data.clone()))
+        } else if let Some(name) = path.strip_prefix(SCENES_PREFIX) {
+            let file_name = match name.strip_prefix(GROUP_SCENE_PREFIX).and_then(|n| n.strip_suffix(".json")) {
+                Some(group_id) => group_ids.get(group_id).map(|id| format!("{}{}.json", GROUP_SCENE_PREFIX, id)),
+                None => remap_session_file(name, &archive_character_ids, &character_ids, &persona_ids),
+            };
+            match file_name {
+                Some(file_name) => Some((SCENES_DIR, file_name, remap_scene_preset(path, data, &preset_ids)?)),
+                None => None,
+            }
+        } else {
+            continue;
+        };
+
+        let Some((dir, file_name, data)) = destination else {
+            report.skipped.push(path.clone());
+            continue;
+        };
+
+        let dest = Path::new(dir).join(&file_name);
+        if (dest.exists() || encrypted_path(&dest).exists()) && strategy != ConflictStrategy::Overwrite {
+            report.skipped.push(path.clone());
+            continue;
+        }
+
+        write_sealed_file(vault, dir, &file_name, &data)?;
+        if dir == HISTORY_DIR || dir == GROUP_HISTORY_DIR {
+            report.histories_imported += 1;
+        }
+    }
+
+    if let Some(data) = files.get(SETTINGS_ENTRY) {
+        if !Path::new(SETTINGS_FILE_PATH).exists() {
+            write_file(Path::new(SETTINGS_FILE_PATH), data)?;
+        }
+    }
+
+    let values: Vec<Value> = existing_characters.into_iter().map(|c| json!(c)).collect();
+    write_characters_file(&values)?;
+    write_personas_file(vault, &existing_personas)?;
+    group::write_groups_file(&existing_groups)?;
+    presets::write_user_presets(&existing_presets)?;
+
+    Ok(report)
+}
+
+/// Remplace le jeton du serveur compatible OpenAI dans un fichier de réglages sérialisé.
+fn with_api_token(data: &[u8], token: &str) -> AppResult<Vec<u8>> {
+    let mut settings: Value =
+        serde_json::from_slice(data).map_err(|e| AppError::parse(SETTINGS_ENTRY, e))?;
+    if let Some(server) = settings.get_mut("openai_server").and_then(Value::as_object_mut) {
+        server.insert("token".to_string(), Value::String(token.to_string()));
+    }
+    serde_json::to_vec_pretty(&settings).map_err(|e| AppError::parse(SETTINGS_ENTRY, e))
+}
+
+/// Jeton d'API des réglages locaux, vide s'ils sont absents ou illisibles.
+fn local_api_token() -> String {
+    fs::read(SETTINGS_FILE_PATH)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+        .and_then(|s| s["openai_server"]["token"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Reporte sur les groupes importés les ids finaux de leurs membres et de leur persona.
+/// Un membre écarté est retiré du groupe ; sans persona ou sans membre, le groupe est écarté.
+fn remap_groups(
+    groups: Vec<GroupChat>,
+    character_ids: &HashMap<String, String>,
+    persona_ids: &HashMap<String, String>,
+    report: &mut ImportReport,
+) -> Vec<GroupChat> {
+    let mut remapped = vec![];
+
+    for mut group in groups {
+        group.character_ids = group
+            .character_ids
+            .iter()
+            .filter_map(|id| character_ids.get(id).cloned())
+            .collect();
+
+        match persona_ids.get(&group.persona_id) {
+            Some(persona_id) if !group.character_ids.is_empty() => {
+                group.persona_id = persona_id.clone();
+                remapped.push(group);
+            }
+            _ => report.skipped.push(format!("group:{}", group.id)),
+        }
+    }
+
+    remapped
+}
+
+/// Fait suivre à une scène importée le renommage éventuel de son préréglage.
+fn remap_scene_preset(path: &str, data: &[u8], preset_ids: &HashMap<String, String>) -> AppResult<Vec<u8>> {
+    let mut scene: SceneState = serde_json::from_slice(data).map_err(|e| AppError::parse(path, e))?;
+    let renamed = scene
+        .preset
+        .as_ref()
+        .and_then(|id| preset_ids.get(id))
+        .filter(|new_id| scene.preset.as_ref() != Some(*new_id))
+        .cloned();
+    let Some(new_id) = renamed else {
+        return Ok(data.to_vec());
+    };
+
+    scene.preset = Some(new_id);
+    serde_json::to_vec_pretty(&scene).map_err(|e| AppError::parse(path, e))
+}
+
+/// Les ids de l'archive deviennent des noms de fichier (historiques, relations, scènes) :
+/// un seul id invalide fait refuser l'archive avant toute écriture.
+fn check_ids(library: &Library) -> AppResult<()> {
+    for character in &library.characters {
+        validate_id("characters.id", &character.id)?;
+    }
+    for persona in &library.personas {
+        validate_id("personas.id", &persona.id)?;
+    }
+    for group in &library.groups {
+        validate_id("groups.id", &group.id)?;
+    }
+    Ok(())
+}
+
+/// Remplace `img` par le chemin final de l'image importée à laquelle il renvoie.
+fn relink_image<T: LibraryEntity>(entity: &mut T, asset_paths: &HashMap<String, String>) {
+    if let Some(img) = entity.img_mut() {
+        if let Some(final_path) = asset_paths.get(img.as_str()) {
+            *img = final_path.clone();
+        }
+    }
+}
+
+/// Les images du magasin passent par `assets::store_image`, qui les décode et les
+/// ré-encode ; leurs miniatures (`<sha256>_<taille>.png`) sont régénérées plutôt que
+/// copiées. Renvoie le chemin public de l'image, `None` pour une miniature.
+fn import_store_image(file_name: &str, data: &[u8], report: &mut ImportReport) -> AppResult<Option<String>> {
+    if file_name.contains('_') {
+        return Ok(None);
+    }
+
+    let existed = Path::new(ASSET_STORE_PATH).join(file_name).exists();
+    let stored = assets::store_image(data)?;
+    if !existed {
+        report.assets_imported += 1;
+    }
+    Ok(Some(stored.id))
+}
+
+/// Copie les images de l'archive et renvoie, pour chacune, le chemin public final
+/// (`/assets/...`) à utiliser dans `img` après résolution des conflits de nom.
+fn merge_assets(
+    files: &ArchiveFiles,
+    strategy: ConflictStrategy,
+    report: &mut ImportReport,
+) -> AppResult<HashMap<String, String>> {
+    let mut asset_paths = HashMap::new();
+
+    for (path, data) in files {
+        if let Some(file_name) = path.strip_prefix(STORE_ASSETS_PREFIX) {
+            if let Some(id) = import_store_image(file_name, data, report)? {
+                asset_paths.insert(format!("/{}", path), id);
+            }
+            continue;
+        }
+        let Some((dir, file_name)) = asset_destination(path) else {
+            continue;
+        };
+        let prefix = &path[..path.len() - file_name.len()];
+        let dest = dir.join(file_name);
+
+        let final_name = match fs::read(&dest) {
+            Err(_) => {
+                write_file(&dest, data)?;
+                report.assets_imported += 1;
+                file_name.to_string()
+            }
+            Ok(existing) if existing == *data => file_name.to_string(),
+            Ok(_) => match strategy {
+                ConflictStrategy::Skip => {
+                    report.skipped.push(path.clone());
+                    file_name.to_string()
+                }
+                ConflictStrategy::Overwrite => {
+                    write_file(&dest, data)?;
+                    report.assets_imported += 1;
+                    file_name.to_string()
+                }
+                ConflictStrategy::Rename => {
+                    let (stem, ext) = file_name.rsplit_once('.').unwrap_or((file_name, ""));
+                    let renamed = unique_id(stem, |candidate| {
+                        dir.join(with_extension(candidate, ext)).exists()
+                    });
+                    let renamed = with_extension(&renamed, ext);
+                    write_file(&dir.join(&renamed), data)?;
+                    report.assets_imported += 1;
+                    renamed
+                }
+            },
+        };
+
+        asset_paths.insert(format!("/{}", path), format!("/{}{}", prefix, final_name));
+    }
+
+    Ok(asset_paths)
+}
+
+/// Fusionne les entités importées dans `existing`. Renvoie la correspondance ancien id →
+/// id local de celles qui existent désormais ici (importées ou déjà identiques), et le
+/// nombre d'entités importées. Une entité écartée n'y figure pas.
+fn merge_entities<T: LibraryEntity + Serialize>(
+    existing: &mut Vec<T>,
+    imported: Vec<T>,
+    asset_paths: &HashMap<String, String>,
+    strategy: ConflictStrategy,
+    kind: &str,
+    report: &mut ImportReport,
+) -> (HashMap<String, String>, usize) {
+    let mut ids = HashMap::new();
+    let mut count = 0;
+
+    for mut entity in imported {
+        relink_image(&mut entity, asset_paths);
+
+        let old_id = entity.id().to_string();
+
+        match existing.iter().position(|e| e.id() == old_id) {
+            None => {
+                existing.push(entity);
+                ids.insert(old_id.clone(), old_id);
+                count += 1;
+            }
+            Some(pos) if serde_json::to_value(&existing[pos]).ok() == serde_json::to_value(&entity).ok() => {
+                ids.insert(old_id.clone(), old_id);
+            }
+            Some(_) if strategy == ConflictStrategy::Skip => {
+                report.skipped.push(format!("{}:{}", kind, old_id));
+            }
+            Some(pos) if strategy == ConflictStrategy::Overwrite => {
+                existing[pos] = entity;
+                ids.insert(old_id.clone(), old_id);
+                count += 1;
+            }
+            Some(_) => {
+                let new_id = unique_id(&old_id, |candidate| existing.iter().any(|e| e.id() == candidate));
+                entity.set_id(new_id.clone());
+                existing.push(entity);
+                report.renamed.push(RenamedEntry {
+                    kind: kind.to_string(),
+                    old_id: old_id.clone(),
+                    new_id: new_id.clone(),
+                });
+                ids.insert(old_id, new_id);
+                count += 1;
+            }
+        }
+    }
+
+    (ids, count)
+}
+
+/// Nom local d'un fichier `<character_id>_<persona_id>.json` de l'archive, ou `None` si son
+/// personnage ou sa persona n'a pas été importé : il rejoindrait sinon un homonyme local.
+/// Les identifiants contenant eux-mêmes des `_`, le découpage s'appuie sur ceux de l'archive.
+fn remap_session_file(
+    file_name: &str,
+    archive_character_ids: &[String],
+    character_ids: &HashMap<String, String>,
+    persona_ids: &HashMap<String, String>,
+) -> Option<String> {
+    let stem = file_name.strip_suffix(".json")?;
+    let (character_id, persona_id) = split_history_stem(stem, archive_character_ids)?;
+
+    Some(format!("{}_{}.json", character_ids.get(&character_id)?, persona_ids.get(&persona_id)?))
+}
+
+fn read_archive(source: &Path) -> AppResult<ArchiveFiles> {
+    let file = fs::File::open(source).map_err(|e| AppError::read(source, e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| AppError::parse(source.display().to_string(), e))?;
+
+    let mut files = ArchiveFiles::new();
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| AppError::parse(source.display().to_string(), e))?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().to_string();
+        if name != MANIFEST_ENTRY && !is_known_entry(&name) {
+            return Err(AppError::validation(tf(Msg::ArchiveEntryNotAllowed, &[&name])));
+        }
+
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| AppError::read(source.join(&name), e))?;
+        files.insert(name, data);
+    }
+
+    let manifest: LibraryManifest = parse_entry(&files, MANIFEST_ENTRY)?;
+    files.remove(MANIFEST_ENTRY);
+
+    if manifest.format_version > LIBRARY_FORMAT_VERSION {
+        return Err(AppError::validation(tf(
+            Msg::UnsupportedArchiveVersion,
+            &[&manifest.format_version, &LIBRARY_FORMAT_VERSION],
+        )));
+    }
+
+    if manifest.files.len() != files.len() {
+        return Err(AppError::validation(t(Msg::ManifestMismatch)));
+    }
+
+    for entry in &manifest.files {
+        let data = files
+            .get(&entry.path)
+            .ok_or_else(|| AppError::not_found(Entity::File, entry.path.as_str()))?;
+        if sha256_hex(data) != entry.sha256 {
+            return Err(AppError::validation(tf(Msg::ChecksumMismatch, &[&entry.path])));
+        }
+    }
+
+    Ok(files)
+}
+
+fn parse_entry<T: serde::de::DeserializeOwned>(files: &ArchiveFiles, entry: &str) -> AppResult<T> {
+    let data = files
+        .get(entry)
+        .ok_or_else(|| AppError::not_found(Entity::File, entry))?;
+
+    serde_json::from_slice(data).map_err(|e| AppError::parse(entry, e))
+}
+
+fn parse_optional_entry<T: serde::de::DeserializeOwned>(files: &ArchiveFiles, entry: &str) -> AppResult<Option<T>> {
+    if !files.contains_key(entry) {
+        return Ok(None);
+    }
+    parse_entry(files, entry).map(Some)
+}
+
+fn check_entry<T: serde::de::DeserializeOwned>(path: &str, data: &[u8]) -> AppResult<()> {
+    serde_json::from_slice::<T>(data)
+        .map(|_| ())
+        .map_err(|e| AppError::parse(path, e))
+}
+
+/// Nom d'un historique de conversation directe, à la racine de `data/history/`.
+fn history_file_name(path: &str) -> Option<&str> {
+    path.strip_prefix(HISTORY_PREFIX).filter(|name| is_plain_file_name(name))
+}
+
+/// N'accepte que les chemins produits par `export_library`, ce qui exclut
+/// toute traversée de répertoire (`../`) ou écriture hors des dossiers de données.
+fn is_known_entry(name: &str) -> bool {
+    let entries = [
+        CHARACTERS_ENTRY,
+        PERSONAS_ENTRY,
+        RECENT_CHATS_ENTRY,
+        SETTINGS_ENTRY,
+        GROUPS_ENTRY,
+        SCENE_PRESETS_ENTRY,
+    ];
+    if entries.contains(&name) {
+        return true;
+    }
+
+    let data = [HISTORY_PREFIX, GROUP_HISTORY_PREFIX, RELATIONSHIPS_PREFIX, SCENES_PREFIX];
+    let images = [CHARACTER_ASSETS_PREFIX, PERSONA_ASSETS_PREFIX, STORE_ASSETS_PREFIX];
+
+    data.iter().filter_map(|prefix| name.strip_prefix(prefix)).any(is_plain_file_name)
+        || images
+            .iter()
+            .filter_map(|prefix| name.strip_prefix(prefix))
+            .any(|file_name| is_plain_file_name(file_name) && is_image_file(file_name))
+}
+
+/// Les dossiers d'images sont servis tels quels par l'interface : rien d'autre n'y entre.
+fn is_image_file(name: &str) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\', ':'])
+}
+
+fn asset_destination(path: &str) -> Option<(PathBuf, &str)> {
+    [
+        (CHARACTER_ASSETS_PREFIX, ASSETS_PATH),
+        (PERSONA_ASSETS_PREFIX, PERSONA_ASSETS_PATH),
+        (STORE_ASSETS_PREFIX, ASSET_STORE_PATH),
+    ]
+    .into_iter()
+    .find_map(|(prefix, dir)| path.strip_prefix(prefix).map(|file_name| (PathBuf::from(dir), file_name)))
+}
+
+fn sealed_destination(path: &str) -> Option<(&'static str, &str)> {
+    SEALED_DIRS
+        .into_iter()
+        .find_map(|(prefix, dir)| path.strip_prefix(prefix).map(|file_name| (dir, file_name)))
+}
+
+/// Noms en clair des fichiers JSON d'un dossier protégé, chiffrés ou non.
+fn sealed_file_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .map(|name| {
+            name.strip_suffix(&format!(".{}", ENCRYPTED_EXTENSION))
+                .map(str::to_string)
+                .unwrap_or(name)
+        })
+        .filter(|name| name.ends_with(".json") && is_plain_file_name(name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn collect_sealed_dir(vault: &Vault, files: &mut ArchiveFiles, prefix: &str, dir: &Path) -> AppResult<()> {
+    for name in sealed_file_names(dir) {
+        if let Some(data) = vault.read(&dir.join(&name))? {
+            files.insert(format!("{}{}", prefix, name), data);
+        }
+    }
+    Ok(())
+}
+
+fn clear_sealed_dir(vault: &Vault, dir: &Path) -> AppResult<()> {
+    for name in sealed_file_names(dir) {
+        vault.remove(&dir.join(name))?;
+    }
+    Ok(())
+}
+
+fn collect_dir(files: &mut ArchiveFiles, prefix: &str, dir: &Path) -> AppResult<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
+            if is_plain_file_name(file_name) {
+                let data = fs::read(&path).map_err(|e| AppError::read(&path, e))?;
+                files.insert(format!("{}{}", prefix, file_name), data);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_sealed_file(vault: &Vault, dir: &str, file_name: &str, data: &[u8]) -> AppResult<()> {
+    fs::create_dir_all(dir).map_err(|e| AppError::create_dir(dir, e))?;
+    vault.write(&Path::new(dir).join(file_name), data)
+}
+
+fn write_file(dest: &Path, data: &[u8]) -> AppResult<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
+    }
+
+    fs::write(dest, data).map_err(|e| AppError::write(dest, e))
+}
+
+fn unique_id(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+fn with_extension(stem: &str, ext: &str) -> String {
+    if ext.is_empty() {
+        stem.to_string()
+    } else {
+        format!("{}.{}", stem, ext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn persona(id: &str, display_name: &str) -> Persona {
+        Persona {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+            background: String::new(),
+            img: "/assets/personas/placeholder.png".to_string(),
+        }
+    }
+
+    fn merge(
+        existing: &mut Vec<Persona>,
+        imported: Vec<Persona>,
+        strategy: ConflictStrategy,
+    ) -> (HashMap<String, String>, usize, ImportReport) {
+        let mut report = ImportReport::default();
+        let (ids, count) = merge_entities(existing, imported, &HashMap::new(), strategy, "persona", &mut report);
+        (ids, count, report)
+    }
+
+    #[test]
+    fn skip_keeps_the_local_entity_and_drops_the_mapping() {
+        let mut existing = vec![persona("bob_1", "Bob")];
+        let imported = vec![persona("bob_1", "Robert"), persona("eve_2", "Eve")];
+        let (ids, count, report) = merge(&mut existing, imported, ConflictStrategy::Skip);
+
+        assert_eq!(existing[0].display_name, "Bob");
+        assert_eq!(existing.len(), 2);
+        assert_eq!(count, 1);
+        assert!(!ids.contains_key("bob_1"));
+        assert_eq!(ids["eve_2"], "eve_2");
+        assert_eq!(report.skipped, vec!["persona:bob_1".to_string()]);
+    }
+
+    #[test]
+    fn identical_entities_map_to_themselves_whatever_the_strategy() {
+        for strategy in [ConflictStrategy::Skip, ConflictStrategy::Overwrite, ConflictStrategy::Rename] {
+            let mut existing = vec![persona("bob_1", "Bob")];
+            let (ids, count, report) = merge(&mut existing, vec![persona("bob_1", "Bob")], strategy);
+
+            assert_eq!(existing.len(), 1);
+            assert_eq!(count, 0);
+            assert_eq!(ids["bob_1"], "bob_1");
+            assert!(report.skipped.is_empty() && report.renamed.is_empty());
+        }
+    }
+
+    #[test]
+    fn overwrite_replaces_the_local_entity() {
+        let mut existing = vec![persona("bob_1", "Bob")];
+        let (ids, count, _) = merge(&mut existing, vec![persona("bob_1", "Robert")], ConflictStrategy::Overwrite);
+
+        assert_eq!(existing.len(), 1);
+        assert_eq!(existing[0].display_name, "Robert");
+        assert_eq!((ids["bob_1"].as_str(), count), ("bob_1", 1));
+    }
+
+    #[test]
+    fn rename_gives_a_fresh_id() {
+        let mut existing = vec![persona("bob_1", "Bob"), persona("bob_1-2", "Bobby")];
+        let (ids, count, report) = merge(&mut existing, vec![persona("bob_1", "Robert")], ConflictStrategy::Rename);
+
+        assert_eq!(existing.len(), 3);
+        assert_eq!((ids["bob_1"].as_str(), count), ("bob_1-3", 1));
+        assert_eq!(report.renamed[0].new_id, "bob_1-3");
+    }
+
+    #[test]
+    fn groups_follow_renamed_members_and_lose_skipped_ones() {
+        let group = |id: &str, members: &[&str], persona_id: &str| GroupChat {
+            id: id.to_string(),
+            name: id.to_string(),
+            character_ids: members.iter().map(|m| m.to_string()).collect(),
+            persona_id: persona_id.to_string(),
+            strategy: Default::default(),
+        };
+        let characters = HashMap::from([("alice_1".to_string(), "alice_1-2".to_string())]);
+        let personas = HashMap::from([("bob_2".to_string(), "bob_2".to_string())]);
+        let mut report = ImportReport::default();
+
+        let groups = remap_groups(
+            vec![
+                group("group_1", &["alice_1", "carol_3"], "bob_2"),
+                group("group_2", &["carol_3"], "bob_2"),
+                group("group_3", &["alice_1"], "dave_4"),
+            ],
+            &characters,
+            &personas,
+            &mut report,
+        );
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].character_ids, vec!["alice_1-2".to_string()]);
+        assert_eq!(report.skipped, vec!["group:group_2".to_string(), "group:group_3".to_string()]);
+    }
+
+    #[test]
+    fn scenes_follow_renamed_presets() {
+        let scene = SceneState {
+            preset: Some("noir_1".to_string()),
+            ..Default::default()
+        };
+        let data = serde_json::to_vec(&scene).unwrap();
+        let presets = HashMap::from([("noir_1".to_string(), "noir_1-2".to_string())]);
+
+        let remapped: SceneState =
+            serde_json::from_slice(&remap_scene_preset("data/scenes/a_1_b_2.json", &data, &presets).unwrap()).unwrap();
+        assert_eq!(remapped.preset.as_deref(), Some("noir_1-2"));
+
+        let untouched = remap_scene_preset("data/scenes/a_1_b_2.json", &data, &HashMap::new()).unwrap();
+        assert_eq!(untouched, data);
+    }
+
+    #[test]
+    fn only_known_archive_paths_are_accepted() {
+        assert!(is_known_entry("data/groups.json"));
+        assert!(is_known_entry("data/history/groups/group_1.json"));
+        assert!(is_known_entry("data/scenes/group-group_1.json"));
+        assert!(is_known_entry("data/history/relationships/a_1_b_2.json"));
+        assert!(!is_known_entry("data/history/../vault.json"));
+        assert!(!is_known_entry("data/scenes/"));
+        assert_eq!(history_file_name("data/history/groups/group_1.json"), None);
+        assert_eq!(history_file_name("data/history/a_1_b_2.json"), Some("a_1_b_2.json"));
+    }
+
+    #[test]
+    fn asset_folders_only_accept_images() {
+        assert!(is_known_entry("assets/characters/alice.PNG"));
+        assert!(is_known_entry("assets/store/0a1b.webp"));
+        assert!(!is_known_entry("assets/characters/index.html"));
+        assert!(!is_known_entry("assets/personas/script.js"));
+        assert!(!is_known_entry("assets/store/noext"));
+    }
+
+    #[test]
+    fn archive_ids_must_be_safe_file_names() {
+        let library = |persona_id: &str| Library {
+            characters: vec![],
+            personas: vec![persona(persona_id, "Bob")],
+            groups: vec![],
+            scene_presets: vec![],
+        };
+
+        assert!(check_ids(&library("bob_1")).is_ok());
+        assert!(check_ids(&library("../../x")).is_err());
+        assert!(check_ids(&library("a/b")).is_err());
+    }
+
+    #[test]
+    fn session_files_follow_renamed_ids() {
+        let archive_ids = vec!["alice_100".to_string()];
+        let characters = HashMap::from([("alice_100".to_string(), "alice_100-2".to_string())]);
+        let personas = HashMap::from([("bob_200".to_string(), "bob_200".to_string())]);
+
+        assert_eq!(
+            remap_session_file("alice_100_bob_200.json", &archive_ids, &characters, &personas),
+            Some("alice_100-2_bob_200.json".to_string())
+        );
+    }
+
+    #[test]
+    fn session_files_of_skipped_entities_are_dropped() {
+        let archive_ids = vec!["alice_100".to_string()];
+        let personas = HashMap::from([("bob_200".to_string(), "bob_200".to_string())]);
+
+        assert_eq!(remap_session_file("alice_100_bob_200.json", &archive_ids, &HashMap::new(), &personas), None);
+
+        let characters = HashMap::from([("alice_100".to_string(), "alice_100".to_string())]);
+        assert_eq!(remap_session_file("alice_100_bob_200.json", &archive_ids, &characters, &HashMap::new()), None);
+    }
+
+    #[test]
+    fn exported_settings_carry_no_api_token() {
+        let data = br#"{"language":"fr","openai_server":{"enabled":true,"port":5100,"token":"secret"}}"#;
+        let settings: Value = serde_json::from_slice(&with_api_token(data, "").unwrap()).unwrap();
+
+        assert_eq!(settings["openai_server"]["token"], "");
+        assert_eq!(settings["openai_server"]["port"], 5100);
+        assert_eq!(settings["language"], "fr");
+    }
+}
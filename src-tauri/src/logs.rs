@@ -0,0 +1,243 @@
+use crate::file_utils::{LOGS_DIR, VAULT_FILE_PATH};
+use crate::types::{LogLevel, LogLine, LogStream, ServiceKind};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+/// Nombre de lignes gardées en mémoire pour chaque service.
+pub const LOG_BUFFER_LINES: usize = 2000;
+pub const MAX_LOG_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// Nombre d'anciens fichiers conservés à la rotation (`go.log.1`, `go.log.2`, ...).
+pub const LOG_FILES_KEPT: usize = 3;
+
+pub const SERVICE_LOG_EVENT: &str = "service-log";
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+/// Journal des services annexes : tampon circulaire en mémoire pour l'interface et
+/// fichiers tournants dans `data/logs` pour le diagnostic après coup. Quand le coffre est
+/// activé, rien n'est écrit sur disque : une ligne d'erreur peut citer une conversation.
+pub struct ServiceLogs {
+    buffers: Mutex<HashMap<ServiceKind, VecDeque<LogLine>>>,
+    files: Mutex<HashMap<ServiceKind, LogFile>>,
+    next_seq: AtomicU64,
+    app: OnceLock<AppHandle>,
+}
+
+impl Default for ServiceLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServiceLogs {
+    pub fn new() -> Self {
+        Self {
+            buffers: Mutex::new(HashMap::new()),
+            files: Mutex::new(HashMap::new()),
+            next_seq: AtomicU64::new(1),
+            app: OnceLock::new(),
+        }
+    }
+
+    pub fn attach(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    /// Lit la sortie standard et d'erreur du processus ligne à ligne dans des threads
+    /// dédiés, ce qui évite aussi qu'un tuyau plein ne bloque le service.
+    pub fn capture(self: &Arc<Self>, kind: ServiceKind, child: &mut Child) {
+        if let Some(stdout) = child.stdout.take() {
+            self.spawn_reader(kind, LogStream::Stdout, stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.spawn_reader(kind, LogStream::Stderr, stderr);
+        }
+    }
+
+    pub fn record(&self, kind: ServiceKind, level: LogLevel, message: &str) {
+        self.push(kind, LogStream::Supervisor, level, message.to_string());
+    }
+
+    /// Lignes postérieures au curseur `since` et au moins aussi graves que `level`.
+    pub fn lines(&self, kind: ServiceKind, since: Option<u64>, level: Option<LogLevel>) -> Vec<LogLine> {
+        let buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(buffer) = buffers.get(&kind) else {
+            return vec![];
+        };
+
+        buffer
+            .iter()
+            .filter(|line| since.map(|s| line.seq > s).unwrap_or(true))
+            .filter(|line| level.map(|l| line.level >= l).unwrap_or(true))
+            .cloned()
+            .collect()
+    }
+
+    fn spawn_reader<R: Read + Send + 'static>(self: &Arc<Self>, kind: ServiceKind, stream: LogStream, source: R) {
+        let logs = Arc::clone(self);
+        thread::spawn(move || {
+            let mut reader = BufReader::new(source);
+            let mut raw = Vec::new();
+            loop {
+                raw.clear();
+                match reader.read_until(b'\n', &mut raw) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        let message = String::from_utf8_lossy(&raw).trim_end().to_string();
+                        if !message.is_empty() {
+                            let level = infer_level(&message);
+                            logs.push(kind, stream, level, message);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn push(&self, kind: ServiceKind, stream: LogStream, level: LogLevel, message: String) {
+        let line = LogLine {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            service: kind,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            stream,
+            level,
+            message,
+        };
+
+        if !Path::new(VAULT_FILE_PATH).exists() {
+            self.write_to_file(&line);
+        }
+
+        {
+            let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+            let buffer = buffers.entry(kind).or_default();
+            if buffer.len() >= LOG_BUFFER_LINES {
+                buffer.pop_front();
+            }
+            buffer.push_back(line.clone());
+        }
+
+        if let Some(app) = self.app.get() {
+            let _ = app.emit(SERVICE_LOG_EVENT, line);
+        }
+    }
+
+    fn write_to_file(&self, line: &LogLine) {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let path = log_path(line.service);
+
+        if files.get(&line.service).map(|f| f.size >= MAX_LOG_FILE_BYTES).unwrap_or(false) {
+            files.remove(&line.service);
+            rotate(&path);
+        }
+
+        let log_file = match files.entry(line.service) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => match open_log_file(&path) {
+                Some(log_file) => entry.insert(log_file),
+                None => return,
+            },
+        };
+
+        let entry = format!(
+            "{} [{}] {:?}: {}\n",
+            line.timestamp,
+            line.level.label(),
+            line.stream,
+            line.message
+        );
+        if log_file.file.write_all(entry.as_bytes()).is_ok() {
+            log_file.size += entry.len() as u64;
+        }
+    }
+}
+
+fn log_path(kind: ServiceKind) -> PathBuf {
+    let name = match kind {
+        ServiceKind::Ollama => "ollama",
+        ServiceKind::Python => "python",
+        ServiceKind::Go => "go",
+    };
+    Path::new(LOGS_DIR).join(format!("{}.log", name))
+}
+
+fn open_log_file(path: &Path) -> Option<LogFile> {
+    fs::create_dir_all(LOGS_DIR).ok()?;
+    let file = OpenOptions::new().create(true).append(true).open(path).ok()?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Some(LogFile { file, size })
+}
+
+/// Décale `x.log` → `x.log.1` → `x.log.2` ..., en supprimant le plus ancien.
+fn rotate(path: &Path) {
+    let numbered = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+    let _ = fs::remove_file(numbered(LOG_FILES_KEPT));
+    for n in (1..LOG_FILES_KEPT).rev() {
+        let _ = fs::rename(numbered(n), numbered(n + 1));
+    }
+    let _ = fs::rename(path, numbered(1));
+}
+
+/// Devine la gravité d'une ligne : Ollama écrit `level=ERROR`, Flask et Go des mots-clés
+/// ou des emojis. Par défaut, tout est `Info` (Flask écrit ses requêtes sur stderr).
+fn infer_level(message: &str) -> LogLevel {
+    let upper = message.to_uppercase();
+
+    if let Some(rest) = upper.split("LEVEL=").nth(1) {
+        if rest.starts_with("ERROR") {
+            return LogLevel::Error;
+        }
+        if rest.starts_with("WARN") {
+            return LogLevel::Warn;
+        }
+        if rest.starts_with("DEBUG") {
+            return LogLevel::Debug;
+        }
+        return LogLevel::Info;
+    }
+
+    if message.contains('❌') || upper.contains("ERROR") || upper.contains("TRACEBACK") || upper.contains("PANIC") {
+        LogLevel::Error
+    } else if message.contains('⚠') || upper.contains("WARN") {
+        LogLevel::Warn
+    } else if upper.contains("DEBUG") {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ollama_level_field_wins() {
+        assert_eq!(infer_level("time=... level=ERROR msg=\"model failed\""), LogLevel::Error);
+        assert_eq!(infer_level("time=... level=WARN msg=\"low memory\""), LogLevel::Warn);
+        assert_eq!(infer_level("time=... level=DEBUG msg=\"error budget\""), LogLevel::Debug);
+        assert_eq!(infer_level("time=... level=INFO msg=\"error count: 0\""), LogLevel::Info);
+    }
+
+    #[test]
+    fn keywords_and_emojis_set_the_level() {
+        assert_eq!(infer_level("❌ [abc] Timeout"), LogLevel::Error);
+        assert_eq!(infer_level("Traceback (most recent call last):"), LogLevel::Error);
+        assert_eq!(infer_level("panic: runtime error"), LogLevel::Error);
+        assert_eq!(infer_level("⚠️ Brouillon refusé"), LogLevel::Warn);
+        assert_eq!(infer_level("DEBUG mode on"), LogLevel::Debug);
+        assert_eq!(infer_level("127.0.0.1 - - \"POST /generate HTTP/1.1\" 200 -"), LogLevel::Info);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::chat;
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils;
+use crate::i18n::{t, Msg};
+use crate::types::{AppState, OpenAiChatRequest};
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::net::Ipv4Addr;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+
+/// Lance le serveur en arrière-plan s'il est activé dans les réglages.
+pub fn spawn(state: AppState) {
+    if !state.settings.get().openai_server.enabled {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(state).await {
+            eprintln!("❌ Serveur compatible OpenAI: {}", e);
+        }
+    });
+}
+
+/// Sert `/v1/models` et `/v1/chat/completions` sur `127.0.0.1`, jusqu'à l'arrêt du processus.
+/// Les personnages y sont présentés comme des modèles.
+pub async fn serve(state: AppState) -> AppResult<()> {
+    let config = state.settings.get().openai_server;
+    if config.token.trim().is_empty() {
+        return Err(AppError::invalid_field("openai_server.token", t(Msg::ApiTokenRequired)));
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))
+        .await
+        .map_err(|e| AppError::conflict(format!("127.0.0.1:{}: {}", config.port, e)))?;
+    println!("🔌 Serveur compatible OpenAI sur http://127.0.0.1:{}/v1", config.port);
+
+    let router = Router::new()
+        .route("/v1/models", get(list_models))
+        .route("/v1/chat/completions", post(chat_completions))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    axum::serve(listener, router)
+        .await
+        .map_err(|e| AppError::backend("OpenAI", None, e.to_string()))
+}
+
+/// Le jeton est relu à chaque requête : le changer dans les réglages suffit à révoquer l'ancien.
+async fn require_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let expected = state.settings.get().openai_server.token;
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if !expected.is_empty() && tokens_match(token, &expected) => next.run(request).await,
+        _ => (StatusCode::UNAUTHORIZED, Json(error_body(t(Msg::ApiUnauthorized), "UNAUTHORIZED"))).into_response(),
+    }
+}
+
+/// Compare les empreintes SHA-256 sans s'arrêter à la première différence : la durée
+/// de la comparaison ne révèle ni le préfixe commun ni la longueur du jeton attendu.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (a, b) = (Sha256::digest(provided), Sha256::digest(expected));
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
+
+async fn list_models() -> AppResult<Json<Value>> {
+    let data: Vec<Value> = file_utils::read_characters_file()?
+        .into_iter()
+        .map(|c| {
+            json!({
+                "id": c.id,
+                "object": "model",
+                "created": 0,
+                "owned_by": "personai",
+                "name": c.name,
+                "description": c.tagline,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "object": "list", "data": data })))
+}
+
+/// Seul le dernier message `user` est transmis : le reste de la conversation est repris de
+/// l'historique enregistré, comme dans l'application.
+async fn chat_completions(State(state): State<AppState>, Json(request): Json<OpenAiChatRequest>) -> AppResult<Response> {
+    let character = file_utils::read_characters_file()?
+        .into_iter()
+        .find(|c| c.id == request.model)
+        .ok_or_else(|| AppError::not_found(Entity::Character, &request.model))?;
+
+    let persona_id = request
+        .user
+        .filter(|id| !id.trim().is_empty())
+        .or(state.settings.get().openai_server.default_persona_id)
+        .ok_or_else(|| AppError::invalid_field("user", t(Msg::ApiNoPersona)))?;
+    if !file_utils::read_personas_file(&state.vault)?.iter().any(|p| p.id == persona_id) {
+        return Err(AppError::not_found(Entity::Persona, persona_id));
+    }
+
+    let input = request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user" && !m.content.trim().is_empty())
+        .map(|m| m.content.clone())
+        .ok_or_else(|| AppError::invalid_field("messages", t(Msg::ApiNoUserMessage)))?;
+
+    let history = file_utils::read_chat_history(&state.vault, &character.id, &persona_id)?;
+    let completion = Completion {
+        id: format!("chatcmpl-{}", state.http.request_id()),
+        model: character.id.clone(),
+        created: chrono::Utc::now().timestamp(),
+    };
+
+    if !request.stream {
+        let answer = chat::send_message(&state, &input, &character.id, &persona_id, history).await?;
+        return Ok(Json(completion.message(&answer)).into_response());
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel::<String>();
+    tauri::async_runtime::spawn(async move {
+        let _ = tx.send(sse(&completion.chunk(json!({ "role": "assistant" }), None)));
+
+        let result = chat::stream_message(&state, &input, &character.id, &persona_id, history, |token| {
+            let _ = tx.send(sse(&completion.chunk(json!({ "content": token }), None)));
+        })
+        .await;
+
+        let last = match result {
+            Ok(_) => completion.chunk(json!({}), Some("stop")),
+            Err(e) => error_body(&e.to_string(), e.code()),
+        };
+        let _ = tx.send(sse(&last));
+        let _ = tx.send("data: [DONE]\n\n".to_string());
+    });
+
+    let body = Body::from_stream(UnboundedReceiverStream::new(rx).map(Ok::<_, Infallible>));
+    Ok((
+        [(header::CONTENT_TYPE, "text/event-stream"), (header::CACHE_CONTROL, "no-cache")],
+        body,
+    )
+        .into_response())
+}
+
+struct Completion {
+    id: String,
+    model: String,
+    created: i64,
+}
+
+impl Completion {
+    fn message(&self, content: &str) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop",
+            }],
+        })
+    }
+
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    }
+}
+
+fn sse(event: &Value) -> String {
+    format!("data: {}\n\n", event)
+}
+
+/// Erreur au format OpenAI ; `code` reprend le code stable de `AppError`.
+fn error_body(message: &str, code: &str) -> Value {
+    json!({
+        "error": {
+            "message": message,
+            "type": code.to_lowercase(),
+            "code": code,
+        }
+    })
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Validation { .. } => StatusCode::BAD_REQUEST,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Backend { .. } => StatusCode::BAD_GATEWAY,
+            AppError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(error_body(&self.to_string(), self.code()))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_match_only_identical_tokens() {
+        assert!(tokens_match("s3cret-token", "s3cret-token"));
+        assert!(!tokens_match("s3cret-tokem", "s3cret-token"));
+        assert!(!tokens_match("s3cret", "s3cret-token"));
+        assert!(!tokens_match("", "s3cret-token"));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::error::{AppError, AppResult, Entity};
+use crate::i18n::{t, tf, Msg};
+use crate::types::{Character, CharacterPreset, Personality};
+
+/// Nombre maximal de messages rappelés au modèle : au-delà, le contexte d'Ollama déborde.
+pub const MAX_MEMORY_DEPTH: u8 = 30;
+
+/// Bibliothèque de personnalités proposées à la création d'un personnage.
+pub fn builtin_presets() -> Vec<CharacterPreset> {
+    let preset = |id: &str, name: Msg, [warmth, verbosity, formality, humor, aggression, memory_depth]: [u8; 6]| {
+        CharacterPreset {
+            id: id.to_string(),
+            name: t(name).to_string(),
+            personality: Personality { warmth, verbosity, formality, humor, aggression, memory_depth },
+        }
+    };
+
+    vec![
+        preset("balanced", Msg::PersonalityBalanced, [50, 50, 50, 50, 50, 5]),
+        preset("companion", Msg::PersonalityCompanion, [85, 55, 30, 55, 10, 8]),
+        preset("mentor", Msg::PersonalityMentor, [45, 40, 80, 20, 20, 12]),
+        preset("trickster", Msg::PersonalityTrickster, [60, 55, 15, 90, 35, 5]),
+        preset("rival", Msg::PersonalityRival, [20, 35, 40, 30, 80, 6]),
+        preset("storyteller", Msg::PersonalityStoryteller, [65, 90, 45, 60, 15, 10]),
+    ]
+}
+
+pub fn find_preset(id: &str) -> AppResult<CharacterPreset> {
+    builtin_presets()
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AppError::not_found(Entity::CharacterPreset, id))
+}
+
+/// Renvoie `character` avec la personnalité du préréglage ; rien n'est enregistré.
+pub fn apply_preset(mut character: Character, preset_id: &str) -> AppResult<Character> {
+    character.personality = Some(find_preset(preset_id)?.personality);
+    Ok(character)
+}
+
+pub fn validate(character: &Character) -> AppResult<()> {
+    let Some(p) = &character.personality else {
+        return Ok(());
+    };
+
+    let axes = [
+        ("warmth", p.warmth),
+        ("verbosity", p.verbosity),
+        ("formality", p.formality),
+        ("humor", p.humor),
+        ("aggression", p.aggression),
+    ];
+    for (axis, value) in axes {
+        check_range(axis, value, 0, 100)?;
+    }
+    check_range("memory_depth", p.memory_depth, 1, MAX_MEMORY_DEPTH)
+}
+
+fn check_range(field: &str, value: u8, min: u8, max: u8) -> AppResult<()> {
+    if (min..=max).contains(&value) {
+        return Ok(());
+    }
+    Err(AppError::invalid_field(
+        &format!("personality.{}", field),
+        tf(Msg::ValueOutOfRange, &[&field, &min, &max]),
+    ))
+}
@@ -0,0 +1,144 @@
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils::{self, SCENE_PRESETS_FILE_PATH};
+use crate::i18n::{t, tf, Msg};
+use crate::types::{SamplingOverrides, ScenePreset, SceneState};
+use std::fs;
+
+/// Préréglages fournis avec l'application ; ceux de l'utilisateur sont dans
+/// `data/scene_presets.json`.
+pub fn builtin_presets() -> Vec<ScenePreset> {
+    let preset = |id: &str, name: Msg, setting: &str, tone: &str, focus: &str, sampling: SamplingOverrides| ScenePreset {
+        id: id.to_string(),
+        name: t(name).to_string(),
+        setting: setting.to_string(),
+        tone: tone.to_string(),
+        focus: focus.to_string(),
+        sampling,
+        builtin: true,
+    };
+
+    vec![
+        preset(
+            "casual",
+            Msg::PresetCasual,
+            "Create a relaxed, everyday environment",
+            "Friendly and approachable",
+            "Natural conversation and character development",
+            SamplingOverrides::default(),
+        ),
+        preset(
+            "dramatic",
+            Msg::PresetDramatic,
+            "Build tension and emotional intensity",
+            "Heightened emotions and meaningful exchanges",
+            "Character depth and emotional revelation",
+            SamplingOverrides { temperature: Some(0.8), ..Default::default() },
+        ),
+        preset(
+            "intimate",
+            Msg::PresetIntimate,
+            "Create a private, personal atmosphere",
+            "Warm, connected, and emotionally open",
+            "Emotional intimacy and vulnerability",
+            SamplingOverrides { temperature: Some(0.75), num_predict: Some(120), ..Default::default() },
+        ),
+        preset(
+            "action",
+            Msg::PresetAction,
+            "Dynamic environment with movement and activity",
+            "Energetic and engaging",
+            "Physical actions and reactive dialogue",
+            SamplingOverrides { temperature: Some(0.85), top_p: Some(0.9), ..Default::default() },
+        ),
+    ]
+}
+
+pub fn read_user_presets() -> AppResult<Vec<ScenePreset>> {
+    match fs::read_to_string(SCENE_PRESETS_FILE_PATH) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| AppError::parse(SCENE_PRESETS_FILE_PATH, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(AppError::read(SCENE_PRESETS_FILE_PATH, e)),
+    }
+}
+
+pub fn write_user_presets(presets: &[ScenePreset]) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(presets).map_err(|e| AppError::parse(SCENE_PRESETS_FILE_PATH, e))?;
+    fs::write(SCENE_PRESETS_FILE_PATH, content).map_err(|e| AppError::write(SCENE_PRESETS_FILE_PATH, e))
+}
+
+pub fn list_presets() -> AppResult<Vec<ScenePreset>> {
+    let mut presets = builtin_presets();
+    presets.extend(read_user_presets()?);
+    Ok(presets)
+}
+
+pub fn find_preset(id: &str) -> AppResult<ScenePreset> {
+    list_presets()?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AppError::not_found(Entity::Preset, id))
+}
+
+/// Préréglage choisi pour la scène, s'il existe encore.
+pub fn preset_for(scene: Option<&SceneState>) -> Option<ScenePreset> {
+    let id = scene?.preset.as_deref()?;
+    find_preset(id).ok()
+}
+
+/// Crée ou remplace un préréglage de l'utilisateur ; sans identifiant, il en reçoit un
+/// tiré de son nom.
+pub fn save_preset(mut preset: ScenePreset) -> AppResult<ScenePreset> {
+    validate(&preset)?;
+
+    if preset.id.trim().is_empty() {
+        preset.id = file_utils::id_from_name(&preset.name, "preset");
+    }
+    if builtin_presets().iter().any(|b| b.id == preset.id) {
+        return Err(AppError::invalid_field("id", tf(Msg::PresetBuiltin, &[&preset.id])));
+    }
+
+    let mut presets = read_user_presets()?;
+    match presets.iter_mut().find(|p| p.id == preset.id) {
+        Some(existing) => *existing = preset.clone(),
+        None => presets.push(preset.clone()),
+    }
+    write_user_presets(&presets)?;
+    Ok(preset)
+}
+
+pub fn delete_preset(id: &str) -> AppResult<()> {
+    if builtin_presets().iter().any(|b| b.id == id) {
+        return Err(AppError::invalid_field("id", tf(Msg::PresetBuiltin, &[&id])));
+    }
+
+    let mut presets = read_user_presets()?;
+    let before = presets.len();
+    presets.retain(|p| p.id != id);
+    if presets.len() == before {
+        return Err(AppError::not_found(Entity::Preset, id));
+    }
+    write_user_presets(&presets)
+}
+
+fn validate(preset: &ScenePreset) -> AppResult<()> {
+    if preset.name.trim().is_empty() {
+        return Err(AppError::invalid_field("name", t(Msg::PresetNameRequired)));
+    }
+
+    let sampling = &preset.sampling;
+    check_range("temperature", sampling.temperature, 0.0, 2.0)?;
+    check_range("top_p", sampling.top_p, 0.0, 1.0)?;
+    check_range("top_k", sampling.top_k.map(|k| k as f32), 1.0, 200.0)?;
+    check_range("repeat_penalty", sampling.repeat_penalty, 0.5, 2.0)?;
+    check_range("num_predict", sampling.num_predict.map(|n| n as f32), 1.0, 4096.0)
+}
+
+fn check_range(field: &str, value: Option<f32>, min: f32, max: f32) -> AppResult<()> {
+    match value {
+        Some(v) if !(min..=max).contains(&v) => Err(AppError::invalid_field(
+            &format!("sampling.{}", field),
+            tf(Msg::ValueOutOfRange, &[&field, &min, &max]),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,169 @@
+use crate::error::{AppError, AppResult};
+use crate::file_utils::{validate_session_ids, RELATIONSHIPS_DIR};
+use crate::types::{Energy, Mood, RelationshipState};
+use crate::vault::Vault;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Indices d'humeur cherchés dans la réplique du personnage, en anglais et en français.
+const MOOD_CUES: &[(Mood, &[&str])] = &[
+    (Mood::Joyful, &["laugh", "smile", "grin", "happy", "glad", "cheerful", "rit", "sourit", "sourire", "heureux", "heureuse", "joie", "ravi", "ravie"]),
+    (Mood::Affectionate, &["blush", "tender", "hug", "gently", "softly", "caress", "rougit", "tendre", "doucement", "câlin", "enlace", "caresse"]),
+    (Mood::Sad, &["sigh", "tear", "sad", "cry", "sob", "soupire", "larme", "triste", "pleure", "sanglote"]),
+    (Mood::Angry, &["glare", "angry", "furious", "snap", "clench", "growl", "colère", "furieux", "furieuse", "fusille", "grogne", "serre les poings"]),
+    (Mood::Afraid, &["tremble", "afraid", "fear", "nervous", "flinch", "frighten", "peur", "nerveux", "nerveuse", "recule", "effrayé", "effrayée"]),
+];
+
+/// Indices cherchés dans le message de l'utilisateur.
+const WARM_CUES: &[&str] = &[
+    "thank", "love", "sorry", "please", "beautiful", "friend", "adore", "merci", "je t'aime", "désolé", "désolée",
+    "s'il te plaît", "ami", "amie", "magnifique",
+];
+const HOSTILE_CUES: &[&str] = &[
+    "hate", "stupid", "shut up", "idiot", "liar", "go away", "useless", "déteste", "stupide", "tais-toi", "menteur",
+    "menteuse", "va-t'en", "dégage", "nul", "nulle",
+];
+const TRUST_CUES: &[&str] = &["promise", "trust", "secret", "honest", "promets", "confiance", "honnête", "confie"];
+
+/// Un seul état par couple personnage/persona, partagé entre la conversation directe et
+/// les groupes où ils se retrouvent.
+pub fn relationship_path(character_id: &str, persona_id: &str) -> AppResult<PathBuf> {
+    validate_session_ids(character_id, persona_id)?;
+    Ok(Path::new(RELATIONSHIPS_DIR).join(format!("{}_{}.json", character_id, persona_id)))
+}
+
+pub fn read_relationship(vault: &Vault, character_id: &str, persona_id: &str) -> AppResult<Option<RelationshipState>> {
+    let path = relationship_path(character_id, persona_id)?;
+    match vault.read(&path)? {
+        Some(data) => serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| AppError::parse(path.display().to_string(), e)),
+        None => Ok(None),
+    }
+}
+
+fn write_relationship(vault: &Vault, character_id: &str, persona_id: &str, state: &RelationshipState) -> AppResult<()> {
+    let path = relationship_path(character_id, persona_id)?;
+    let content = serde_json::to_string_pretty(state).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+
+    fs::create_dir_all(RELATIONSHIPS_DIR).map_err(|e| AppError::create_dir(RELATIONSHIPS_DIR, e))?;
+    vault.write(&path, content.as_bytes())
+}
+
+pub fn reset_relationship(vault: &Vault, character_id: &str, persona_id: &str) -> AppResult<()> {
+    vault.remove(&relationship_path(character_id, persona_id)?)
+}
+
+/// Met à jour l'état après un échange et l'enregistre.
+pub fn record_turn(vault: &Vault, character_id: &str, persona_id: &str, input: &str, reply: &str) -> AppResult<RelationshipState> {
+    let previous = read_relationship(vault, character_id, persona_id)?.unwrap_or_default();
+    let mut next = classify_turn(&previous, input, reply);
+    next.updated_at = Some(chrono::Local::now().to_rfc3339());
+
+    write_relationship(vault, character_id, persona_id, &next)?;
+    Ok(next)
+}
+
+/// Classifieur par mots-clés : pas d'appel au modèle, donc rien à attendre après la réponse.
+/// Les écarts par tour sont bornés pour que l'état évolue progressivement.
+pub fn classify_turn(previous: &RelationshipState, input: &str, reply: &str) -> RelationshipState {
+    let warm = count_cues(input, WARM_CUES).min(3);
+    let hostile = count_cues(input, HOSTILE_CUES).min(3);
+    let trusting = count_cues(input, TRUST_CUES).min(2);
+
+    let mood = MOOD_CUES
+        .iter()
+        .map(|(mood, cues)| (*mood, count_cues(reply, cues)))
+        .filter(|(_, hits)| *hits > 0)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(mood, _)| mood)
+        .unwrap_or(Mood::Neutral);
+
+    let exclamations = reply.matches('!').count();
+    let trailing = reply.matches("...").count() + reply.matches('…').count();
+    let energy = if exclamations >= 2 {
+        Energy::High
+    } else if trailing >= 2 {
+        Energy::Low
+    } else {
+        Energy::Moderate
+    };
+
+    let mood_tension = match mood {
+        Mood::Angry | Mood::Afraid => 10,
+        Mood::Joyful | Mood::Affectionate => -5,
+        Mood::Sad | Mood::Neutral => 0,
+    };
+
+    RelationshipState {
+        mood,
+        energy,
+        affinity: (previous.affinity + 2 * warm - 4 * hostile).clamp(-100, 100),
+        trust: (previous.trust + 2 * trusting + i32::from(warm > 0) - 3 * hostile).clamp(0, 100),
+        // La tension retombe d'elle-même si rien ne l'entretient.
+        tension: (previous.tension - 2 + mood_tension + 8 * hostile).clamp(0, 100),
+        turns: previous.turns + 1,
+        updated_at: previous.updated_at.clone(),
+    }
+}
+
+/// Les indices d'un mot sont comparés aux mots du texte (ou à leur début pour les indices
+/// d'au moins quatre lettres : `laugh` couvre `laughs`) ; les expressions, au texte entier.
+fn count_cues(text: &str, cues: &[&str]) -> i32 {
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '-').collect();
+
+    cues.iter()
+        .filter(|cue| {
+            if cue.contains([' ', '\'', '-']) {
+                text.contains(**cue)
+            } else if cue.chars().count() >= 4 {
+                words.iter().any(|w| w.starts_with(**cue))
+            } else {
+                words.iter().any(|w| w == *cue)
+            }
+        })
+        .count() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_cues_match_whole_words_and_long_ones_prefixes() {
+        assert_eq!(count_cues("Thanks, friend! I love you.", WARM_CUES), 3);
+        assert_eq!(count_cues("Un geste amical.", WARM_CUES), 0);
+        assert_eq!(count_cues("Je t'aime, mon ami.", WARM_CUES), 2);
+    }
+
+    #[test]
+    fn relationship_path_rejects_ids_leaving_its_folder() {
+        assert!(relationship_path("alice_1", "bob_1").is_ok());
+        assert!(relationship_path("../history/alice", "bob").is_err());
+        assert!(relationship_path("alice_1", "..\\bob").is_err());
+    }
+
+    #[test]
+    fn hostile_turn_lowers_affinity_and_raises_tension() {
+        let state = classify_turn(&RelationshipState::default(), "I hate you, idiot.", "She glares and growls!");
+
+        assert_eq!(state.mood, Mood::Angry);
+        assert_eq!(state.energy, Energy::Moderate);
+        assert_eq!(state.affinity, -8);
+        assert_eq!(state.trust, 44);
+        assert_eq!(state.tension, 24);
+        assert_eq!(state.turns, 1);
+    }
+
+    #[test]
+    fn neutral_turn_lets_tension_decay() {
+        let previous = RelationshipState { tension: 10, ..Default::default() };
+        let state = classify_turn(&previous, "On avance.", "Il hoche la tête... puis repart...");
+
+        assert_eq!(state.mood, Mood::Neutral);
+        assert_eq!(state.energy, Energy::Low);
+        assert_eq!(state.tension, 8);
+        assert_eq!(state.affinity, 0);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::error::{AppError, AppResult};
+use crate::file_utils::{self, SCENES_DIR};
+use crate::group;
+use crate::http::Retry;
+use crate::presets;
+use crate::types::{
+    AppState, AskResponse, ChatMessage, ChatSession, NarrateRequest, Narration, SceneChanged, SceneState, ServiceKind,
+};
+use crate::vault::Vault;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Émis quand l'état d'une scène change, par une commande ou par le narrateur.
+pub const SCENE_CHANGED_EVENT: &str = "scene-changed";
+
+/// Rôle des messages du narrateur dans les historiques.
+pub const NARRATOR_ROLE: &str = "narrator";
+
+/// Préfixe des fichiers de scène des conversations de groupe.
+pub const GROUP_SCENE_PREFIX: &str = "group-";
+
+const SCENE_BLOCK_START: &str = "[scene]";
+const SCENE_BLOCK_END: &str = "[/scene]";
+
+/// Les ids de la session viennent de l'interface : ils sont vérifiés avant de devenir un
+/// nom de fichier.
+pub fn scene_path(session: &ChatSession) -> AppResult<PathBuf> {
+    let name = match session {
+        ChatSession::Direct { character_id, persona_id } => {
+            file_utils::validate_session_ids(character_id, persona_id)?;
+            format!("{}_{}.json", character_id, persona_id)
+        }
+        ChatSession::Group { group_id } => {
+            file_utils::validate_id("group_id", group_id)?;
+            format!("{}{}.json", GROUP_SCENE_PREFIX, group_id)
+        }
+    };
+    Ok(Path::new(SCENES_DIR).join(name))
+}
+
+pub fn read_scene(vault: &Vault, session: &ChatSession) -> AppResult<Option<SceneState>> {
+    let path = scene_path(session)?;
+    match vault.read(&path)? {
+        Some(data) => serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| AppError::parse(path.display().to_string(), e)),
+        None => Ok(None),
+    }
+}
+
+pub fn write_scene(vault: &Vault, session: &ChatSession, mut scene: SceneState) -> AppResult<SceneState> {
+    let path = scene_path(session)?;
+    scene.updated_at = Some(chrono::Local::now().to_rfc3339());
+    let content = serde_json::to_string_pretty(&scene).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+
+    fs::create_dir_all(SCENES_DIR).map_err(|e| AppError::create_dir(SCENES_DIR, e))?;
+    vault.write(&path, content.as_bytes())?;
+    Ok(scene)
+}
+
+/// Change le préréglage de la scène en cours ; `None` revient au ton par défaut.
+pub fn set_preset(vault: &Vault, session: &ChatSession, preset_id: Option<&str>) -> AppResult<SceneState> {
+    if let Some(id) = preset_id {
+        presets::find_preset(id)?;
+    }
+
+    let mut scene = read_scene(vault, session)?.unwrap_or_default();
+    scene.preset = preset_id.map(str::to_string);
+    write_scene(vault, session, scene)
+}
+
+pub fn notify(app: &AppHandle, session: ChatSession, scene: SceneState) {
+    let _ = app.emit(SCENE_CHANGED_EVENT, SceneChanged { session, scene });
+}
+
+/// Fait intervenir le narrateur dans la conversation, guidé par `direction` si elle est
+/// donnée. Sa réponse est ajoutée à l'historique ; le bloc `[SCENE]` qui la termine met à
+/// jour l'état de la scène et n'est pas conservé dans le message.
+pub async fn narrate(state: &AppState, session: &ChatSession, direction: Option<&str>) -> AppResult<Narration> {
+    let (character_ids, persona_id, history) = match session {
+        ChatSession::Direct { character_id, persona_id } => (
+            vec![character_id.clone()],
+            persona_id.clone(),
+            file_utils::read_chat_history(&state.vault, character_id, persona_id)?,
+        ),
+        ChatSession::Group { group_id } => {
+            let group = group::find_group(group_id)?;
+            let history = group::read_group_history(&state.vault, group_id)?;
+            (group.character_ids, group.persona_id, history)
+        }
+    };
+
+    let previous = read_scene(&state.vault, session)?.unwrap_or_default();
+    let persona = file_utils::read_personas_file(&state.vault)?.into_iter().find(|p| p.id == persona_id);
+    let settings = state.settings.get();
+    let request = NarrateRequest {
+        character_ids,
+        persona_id,
+        direction: direction.map(str::trim).unwrap_or_default().to_string(),
+        scene: previous.clone(),
+        scene_preset: presets::preset_for(Some(&previous)),
+        persona,
+        memory: history,
+        model: settings.model,
+        language: settings.locale,
+    };
+
+    let url = format!("{}/narrate", state.supervisor.base_url(ServiceKind::Go));
+    let answer = state
+        .http
+        .post_json::<NarrateRequest, AskResponse>(ServiceKind::Go, &url, &request, Retry::ConnectOnly)
+        .await?
+        .answer;
+
+    let mut scene = previous.clone();
+    let text = apply_scene_block(&mut scene, &answer);
+    let scene_changed = scene != previous;
+    let scene = if scene_changed { write_scene(&state.vault, session, scene)? } else { scene };
+
+    let message = ChatMessage {
+        role: NARRATOR_ROLE.to_string(),
+        content: text,
+        timestamp: Some(chrono::Local::now().to_rfc3339()),
+        speaker_id: None,
+    };
+    match session {
+        ChatSession::Direct { character_id, persona_id } => {
+            file_utils::append_chat_messages(&state.vault, character_id, persona_id, vec![message.clone()])?
+        }
+        ChatSession::Group { group_id } => group::append_group_messages(&state.vault, group_id, vec![message.clone()])?,
+    }
+
+    Ok(Narration { message, scene, scene_changed })
+}
+
+/// Retire de `text` le bloc `[SCENE] … [/SCENE]` et en reporte les champs dans `scene`.
+/// Les listes sont séparées par des `;` ; un champ absent garde sa valeur.
+pub fn apply_scene_block(scene: &mut SceneState, text: &str) -> String {
+    let Some(start) = find_marker(text, SCENE_BLOCK_START, 0) else {
+        return text.trim().to_string();
+    };
+    let end = find_marker(text, SCENE_BLOCK_END, start);
+
+    let block = &text[start + SCENE_BLOCK_START.len()..end.unwrap_or(text.len())];
+    for line in block.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_lowercase().as_str() {
+            "location" => scene.location = value.to_string(),
+            "time" | "time_of_day" => scene.time_of_day = value.to_string(),
+            "present" | "present_characters" => scene.present_characters = split_list(value),
+            "objectives" => scene.objectives = split_list(value),
+            _ => {}
+        }
+    }
+
+    let rest = end.map(|i| &text[i + SCENE_BLOCK_END.len()..]).unwrap_or_default();
+    format!("{}\n{}", text[..start].trim(), rest.trim()).trim().to_string()
+}
+
+/// Position de `marker` (ASCII) dans `text` à partir de `from`, sans tenir compte de la casse.
+fn find_marker(text: &str, marker: &str, from: usize) -> Option<usize> {
+    (from..text.len()).find(|&i| text.get(i..i + marker.len()).is_some_and(|s| s.eq_ignore_ascii_case(marker)))
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty() && !item.eq_ignore_ascii_case("none"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_is_removed_and_its_fields_applied() {
+        let mut scene = SceneState { location: "Taverne".to_string(), ..Default::default() };
+        let text = "La porte grince.\n[SCENE]\nTime: nuit\nPresent: Alice; Bob ; none\nObjectives: fuir\n[/SCENE]\nUn silence tombe.";
+
+        assert_eq!(apply_scene_block(&mut scene, text), "La porte grince.\nUn silence tombe.");
+        assert_eq!(scene.location, "Taverne");
+        assert_eq!(scene.time_of_day, "nuit");
+        assert_eq!(scene.present_characters, vec!["Alice", "Bob"]);
+        assert_eq!(scene.objectives, vec!["fuir"]);
+    }
+
+    #[test]
+    fn unterminated_block_swallows_the_rest() {
+        let mut scene = SceneState::default();
+        assert_eq!(apply_scene_block(&mut scene, "Récit.\n[scene]\nlocation: Port"), "Récit.");
+        assert_eq!(scene.location, "Port");
+    }
+
+    #[test]
+    fn scene_path_rejects_ids_leaving_the_scenes_folder() {
+        let direct = |character_id: &str| ChatSession::Direct {
+            character_id: character_id.to_string(),
+            persona_id: "bob_1".to_string(),
+        };
+
+        assert!(scene_path(&direct("alice_1")).is_ok());
+        assert!(scene_path(&direct("../../x")).is_err());
+        assert!(scene_path(&ChatSession::Group { group_id: "../groups".to_string() }).is_err());
+    }
+
+    #[test]
+    fn text_without_block_leaves_the_scene_alone() {
+        let mut scene = SceneState::default();
+        assert_eq!(apply_scene_block(&mut scene, "  Rien ne change.  "), "Rien ne change.");
+        assert_eq!(scene, SceneState::default());
+    }
+}
@@ -1,204 +1,253 @@
-use std::process::{Command, Stdio};
+use crate::types::{ServiceKind, ServiceStartError};
+use reqwest::blocking::Client;
+use std::io;
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
 use std::thread;
-use std::time::Duration;
-
-pub const API_BASE_URL: &str = "http://localhost:8080";
-pub const SERVICE_STARTUP_DELAY: u64 = 2;
-pub const OLLAMA_STARTUP_DELAY: u64 = 5; 
-
-pub fn start_ollama_service() {
-    thread::spawn(|| {
-        println!("🚀 Démarrage d'Ollama...");
-        let mut child = Command::new("ollama")
-            .arg("serve")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("❌ Échec du lancement d'Ollama - Assurez-vous qu'Ollama est installé");
-
-        let _ = child.wait();
-    });
+use std::time::{Duration, Instant};
+
+pub const SERVICE_STOP_TIMEOUT: u64 = 3;
+pub const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+pub const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+/// Nombre de ports essayés après le port par défaut quand celui-ci est pris par un tiers.
+pub const PORT_SEARCH_RANGE: u16 = 20;
+
+/// Valeur du champ `service` renvoyé par `/health`, qui permet de reconnaître nos services.
+pub const PYTHON_SERVICE_SIGNATURE: &str = "PersonAI LLM Service";
+pub const GO_SERVICE_SIGNATURE: &str = "PersonAI Go API";
+
+/// Ce qui écoute sur le port d'un service avant qu'on le lance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortOccupant {
+    Free,
+    /// Une instance de notre service, lancée hors de l'application : on l'adopte.
+    Ours,
+    Foreign,
 }
 
-pub fn start_python_service() {
-    thread::spawn(|| {
-        println!("🐍 Démarrage du service Python LLM...");
-        
-        let python_commands = if cfg!(windows) {
-            vec!["py", "python", "python3"]
-        } else {
-            vec!["python3", "python"]
-        };
-        
-        let mut python_cmd = None;
-        for cmd in python_commands {
-            if Command::new(cmd).arg("--version").output().is_ok() {
-                python_cmd = Some(cmd);
-                break;
-            }
-        }
-        
-        let python_cmd = python_cmd.unwrap_or_else(|| {
-            eprintln!("❌ Aucune installation Python trouvée");
-            eprintln!("💡 Installez Python depuis https://python.org ou Microsoft Store");
-            std::process::exit(1);
-        });
-        
-        println!("🐍 Utilisation de la commande Python: {}", python_cmd);
-        
-        let mut child = Command::new(python_cmd)
-            .arg("python-llm/app.py")
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .unwrap_or_else(|e| {
-                eprintln!("❌ Échec du lancement du service Python avec '{}': {}", python_cmd, e);
-                std::process::exit(1);
-            });
-
-        let _ = child.wait();
-    });
+/// Service appelé par `kind`, dont l'adresse lui est transmise au lancement.
+pub fn upstream(kind: ServiceKind) -> Option<ServiceKind> {
+    match kind {
+        ServiceKind::Ollama => None,
+        ServiceKind::Python => Some(ServiceKind::Ollama),
+        ServiceKind::Go => Some(ServiceKind::Python),
+    }
 }
 
-pub fn start_go_service() {
-    thread::spawn(|| {
-        println!("🔧 Démarrage du service Go API...");
-        let mut child = Command::new("go")
-            .args(&["run", "main.go"])
-            .current_dir("go-api")
-            .stdout(Stdio::inherit()) // Affiche les logs Go
-            .stderr(Stdio::inherit())
-            .spawn()
-            .expect("❌ Échec du lancement du service Go");
-
-        let _ = child.wait();
-    });
+pub fn base_url(port: u16) -> String {
+    format!("http://localhost:{}", port)
 }
 
-
-pub fn start_all_services() {
-    println!("🚀 Démarrage de tous les services...");
-    
-    
-    start_ollama_service();
-    println!("⏳ Attente du démarrage d'Ollama...");
-    thread::sleep(Duration::from_secs(OLLAMA_STARTUP_DELAY));
-    
-    
-    start_python_service();
-    println!("⏳ Attente du démarrage du service Python...");
-    thread::sleep(Duration::from_secs(SERVICE_STARTUP_DELAY));
-    
-   
-    start_go_service();
-    println!("⏳ Attente du démarrage du service Go...");
-    thread::sleep(Duration::from_secs(SERVICE_STARTUP_DELAY));
-    
-    println!("✅ Tous les services sont en cours de démarrage");
+pub fn service_health_url(kind: ServiceKind, port: u16) -> String {
+    let path = match kind {
+        ServiceKind::Ollama => "/api/tags",
+        ServiceKind::Python | ServiceKind::Go => "/health",
+    };
+    format!("{}{}", base_url(port), path)
 }
 
-
-pub async fn check_ollama_health() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/tags";
-
-    match client.get(&*url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                Ok("Ollama opérationnel".to_string())
-            } else {
-                Err(format!("Ollama répond avec le statut: {}", response.status()))
-            }
-        }
-        Err(e) => Err(format!("Ollama non disponible: {}", e)),
+/// Temps maximal accordé à chaque service pour répondre ; `go run` compile avant de démarrer.
+pub fn readiness_timeout(kind: ServiceKind) -> Duration {
+    match kind {
+        ServiceKind::Ollama => Duration::from_secs(30),
+        ServiceKind::Python => Duration::from_secs(30),
+        ServiceKind::Go => Duration::from_secs(120),
     }
 }
 
-pub async fn make_http_request<T, R>(endpoint: &str, payload: &T) -> Result<R, String>
-where
-    T: serde::Serialize,
-    R: for<'de> serde::Deserialize<'de>,
-{
-    let client = reqwest::Client::new();
-    let url = format!("{}{}", API_BASE_URL, endpoint);
-
-    let response = client
-        .post(&url)
-        .json(payload)
-        .send()
-        .await
-        .map_err(|e| format!("Erreur de connexion à l'API: {}", e))?;
-
-    if response.status().is_success() {
-        response
-            .json::<R>()
-            .await
-            .map_err(|e| format!("Erreur de parsing de la réponse: {}", e))
-    } else {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "Impossible de lire le corps de la réponse".to_string());
-        Err(format!("Erreur API {}: {}", status, body))
-    }
+pub fn port_in_use(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_err()
 }
 
-pub async fn make_simple_post_request<T>(endpoint: &str, payload: &T) -> Result<String, String>
-where
-    T: serde::Serialize,
-{
-    let client = reqwest::Client::new();
-    let url = format!("{}{}", API_BASE_URL, endpoint);
+pub fn find_free_port(from: u16) -> Option<u16> {
+    (from..from.saturating_add(PORT_SEARCH_RANGE)).find(|port| !port_in_use(*port))
+}
 
-    let response = client
-        .post(&url)
-        .json(payload)
-        .send()
-        .await
-        .map_err(|e| format!("Erreur de connexion: {}", e))?;
+/// Interroge l'endpoint de santé du port pour savoir si c'est l'un de nos services qui l'occupe.
+pub fn identify_port_occupant(client: &Client, kind: ServiceKind, port: u16) -> PortOccupant {
+    if !port_in_use(port) {
+        return PortOccupant::Free;
+    }
 
-    if response.status().is_success() {
-        Ok("Opération réussie".to_string())
+    let body = client
+        .get(service_health_url(kind, port))
+        .send()
+        .ok()
+        .filter(|response| response.status().is_success())
+        .and_then(|response| response.json::<serde_json::Value>().ok());
+
+    let Some(body) = body else {
+        return PortOccupant::Foreign;
+    };
+
+    let recognised = match kind {
+        ServiceKind::Ollama => body.get("models").is_some_and(|m| m.is_array()),
+        ServiceKind::Python => body["service"] == PYTHON_SERVICE_SIGNATURE,
+        ServiceKind::Go => body["service"] == GO_SERVICE_SIGNATURE,
+    };
+
+    if recognised {
+        PortOccupant::Ours
     } else {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "Impossible de lire le corps de la réponse".to_string());
-        Err(format!("Erreur {}: {}", status, body))
+        PortOccupant::Foreign
     }
 }
 
-pub async fn check_service_health() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/health", API_BASE_URL);
+/// Lance le service sur `port` ; `upstream_url` est l'adresse du service qu'il appelle.
+pub fn spawn_service(kind: ServiceKind, port: u16, upstream_url: Option<String>) -> Result<Child, ServiceStartError> {
+    let mut command = match kind {
+        ServiceKind::Ollama => ollama_command(port),
+        ServiceKind::Python => python_command(port)?,
+        ServiceKind::Go => go_command(port),
+    };
+
+    if let Some(url) = upstream_url {
+        let var = match kind {
+            ServiceKind::Python => "OLLAMA_URL",
+            _ => "PYTHON_LLM_URL",
+        };
+        command.env(var, url);
+    }
 
-    match client.get(&url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                Ok("Services opérationnels".to_string())
-            } else {
-                Ok(format!("Services répondent avec le statut: {}", response.status()))
+    spawn_in_own_group(&mut command).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            ServiceStartError::ExecutableNotFound {
+                service: kind,
+                program: command.get_program().to_string_lossy().into_owned(),
+            }
+        } else {
+            ServiceStartError::SpawnFailed {
+                service: kind,
+                message: e.to_string(),
             }
         }
-        Err(_) => Ok("Services non disponibles".to_string()),
-    }
+    })
 }
 
+fn ollama_command(port: u16) -> Command {
+    println!("🚀 Démarrage d'Ollama sur le port {}...", port);
+    let mut command = Command::new("ollama");
+    command
+        .arg("serve")
+        .env("OLLAMA_HOST", format!("127.0.0.1:{}", port))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
 
-pub async fn check_all_services_health() -> Result<String, String> {
-    let mut results = Vec::new();
+    command
+}
+
+fn python_command(port: u16) -> Result<Command, ServiceStartError> {
+    println!("🐍 Démarrage du service Python LLM sur le port {}...", port);
     
+    let python_commands = if cfg!(windows) {
+        vec!["py", "python", "python3"]
+    } else {
+        vec!["python3", "python"]
+    };
     
-    match check_ollama_health().await {
-        Ok(msg) => results.push(format!("🤖 Ollama: {}", msg)),
-        Err(msg) => results.push(format!("❌ Ollama: {}", msg)),
-    }
+    let python_cmd = python_commands
+        .into_iter()
+        .find(|cmd| Command::new(cmd).arg("--version").output().is_ok())
+        .ok_or_else(|| ServiceStartError::ExecutableNotFound {
+            service: ServiceKind::Python,
+            program: "python3".to_string(),
+        })?;
     
+    println!("🐍 Utilisation de la commande Python: {}", python_cmd);
     
-    match check_service_health().await {
-        Ok(msg) => results.push(format!("🔧 Services: {}", msg)),
-        Err(msg) => results.push(format!("❌ Services: {}", msg)),
+    let mut command = Command::new(python_cmd);
+    command
+        .arg("python-llm/app.py")
+        .env("PORT", port.to_string())
+        // Sans cela, Python garde sa sortie en tampon tant qu'elle va dans un tuyau.
+        .env("PYTHONUNBUFFERED", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    Ok(command)
+}
+
+fn go_command(port: u16) -> Command {
+    println!("🔧 Démarrage du service Go API sur le port {}...", port);
+    let mut command = Command::new("go");
+    command
+        .args(["run", "main.go"])
+        .current_dir("go-api")
+        .env("PORT", port.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    command
+}
+
+/// `go run` lance à son tour le binaire compilé : on place chaque service dans son propre
+/// groupe de processus pour pouvoir arrêter toute la descendance d'un coup.
+fn spawn_in_own_group(command: &mut Command) -> io::Result<Child> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
     }
-    
-    Ok(results.join("\n"))
+
+    command.spawn()
 }
 
-fn port_in_use(p: u16) -> bool {
-    std::net::TcpListener::bind(("127.0.0.1", p)).is_err()
-}
\ No newline at end of file
+/// Demande poliment l'arrêt du service puis le tue s'il n'est pas sorti à temps.
+pub fn terminate_child(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGTERM);
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(SERVICE_STOP_TIMEOUT);
+    while Instant::now() < deadline {
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGKILL);
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+
+/// Attend que le port du service accepte les connexions puis que son endpoint de santé
+/// réponde. `alive` permet d'abandonner tôt si le processus meurt pendant l'attente.
+pub fn probe_ready(
+    client: &Client,
+    kind: ServiceKind,
+    port: u16,
+    alive: impl Fn() -> bool,
+) -> Result<Duration, ServiceStartError> {
+    let started = Instant::now();
+    let timeout = readiness_timeout(kind);
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    loop {
+        if TcpStream::connect_timeout(&addr, PROBE_CONNECT_TIMEOUT).is_ok() {
+            if let Ok(response) = client.get(service_health_url(kind, port)).send() {
+                if response.status().is_success() {
+                    return Ok(started.elapsed());
+                }
+            }
+        }
+
+        if !alive() {
+            return Err(ServiceStartError::ExitedDuringStartup { service: kind });
+        }
+        if started.elapsed() >= timeout {
+            return Err(ServiceStartError::NotReady {
+                service: kind,
+                timeout_secs: timeout.as_secs(),
+            });
+        }
+
+        thread::sleep(PROBE_INTERVAL);
+    }
+}
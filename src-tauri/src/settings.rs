@@ -0,0 +1,255 @@
+use crate::error::{AppError, AppResult};
+use crate::file_utils::SETTINGS_FILE_PATH;
+use crate::i18n::{self, t, tf, Msg};
+use crate::types::Settings;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Plage des ports acceptés ; le dernier port reste libre pour le repli sur `port + 1`.
+pub const MIN_PORT: u16 = 1024;
+pub const MAX_PORT: u16 = 65534;
+/// Au-delà, le délai plafonné à une minute ferait insister le superviseur pendant des heures.
+pub const MAX_RESTARTS: u32 = 20;
+/// Une semaine : au-delà, la rétention horaire et quotidienne n'aurait plus de sens.
+pub const MAX_SNAPSHOT_INTERVAL_MINUTES: u64 = 7 * 24 * 60;
+
+/// Réglages courants, partagés par les commandes et les tâches de fond qui les relisent
+/// à chaque utilisation : une modification s'applique donc sans redémarrer l'application.
+/// Les variables d'environnement `PERSONAI_*` ont priorité sur le fichier.
+pub struct SettingsStore {
+    current: RwLock<Settings>,
+    app: OnceLock<AppHandle>,
+}
+
+impl SettingsStore {
+    /// Charge `data/settings.json` ; un fichier absent ou invalide donne les valeurs par défaut.
+    pub fn load() -> Self {
+        let from_file = match read_settings_file() {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("⚠️ Réglages ignorés, valeurs par défaut utilisées: {}", e);
+                Settings::default()
+            }
+        };
+
+        let settings = match validate(&from_file) {
+            Ok(()) => from_file,
+            Err(e) => {
+                eprintln!("⚠️ Réglages invalides, valeurs par défaut utilisées: {}", e);
+                Settings::default()
+            }
+        };
+        let settings = with_env_overrides(settings);
+
+        i18n::set_locale(settings.locale);
+        Self {
+            current: RwLock::new(settings),
+            app: OnceLock::new(),
+        }
+    }
+
+    pub fn attach(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    pub fn get(&self) -> Settings {
+        self.current.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Valide et enregistre les réglages, puis les rend effectifs et émet `settings-changed`.
+    /// Renvoie les réglages effectifs, variables d'environnement comprises.
+    pub fn update(&self, settings: Settings) -> AppResult<Settings> {
+        validate(&settings)?;
+
+        let content = serde_json::to_string_pretty(&settings)
+            .map_err(|e| AppError::parse(SETTINGS_FILE_PATH, e))?;
+        fs::write(SETTINGS_FILE_PATH, content).map_err(|e| AppError::write(SETTINGS_FILE_PATH, e))?;
+
+        let effective = with_env_overrides(settings);
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = effective.clone();
+        i18n::set_locale(effective.locale);
+
+        if let Some(app) = self.app.get() {
+            let _ = app.emit(SETTINGS_CHANGED_EVENT, effective.clone());
+        }
+        Ok(effective)
+    }
+}
+
+pub fn validate(settings: &Settings) -> AppResult<()> {
+    if settings.model.trim().is_empty() {
+        return Err(AppError::invalid_field("model", t(Msg::ModelRequired)));
+    }
+
+    let mut ports = vec![settings.ollama_port, settings.python_port, settings.go_port];
+    if settings.openai_server.enabled {
+        if settings.openai_server.token.trim().is_empty() {
+            return Err(AppError::invalid_field("openai_server.token", t(Msg::ApiTokenRequired)));
+        }
+        ports.push(settings.openai_server.port);
+    }
+    if ports.iter().any(|p| !(MIN_PORT..=MAX_PORT).contains(p)) {
+        return Err(AppError::invalid_field("ports", tf(Msg::PortsOutOfRange, &[&MIN_PORT, &MAX_PORT])));
+    }
+    if ports.iter().collect::<HashSet<_>>().len() != ports.len() {
+        return Err(AppError::invalid_field("ports", t(Msg::PortsNotDistinct)));
+    }
+
+    if !(1..=3600).contains(&settings.health_interval_secs) {
+        return Err(AppError::invalid_field("health_interval_secs", t(Msg::HealthIntervalRange)));
+    }
+    if settings.max_restarts > MAX_RESTARTS {
+        return Err(AppError::invalid_field("max_restarts", tf(Msg::MaxRestartsRange, &[&MAX_RESTARTS])));
+    }
+    if !(1..=MAX_SNAPSHOT_INTERVAL_MINUTES).contains(&settings.snapshot_interval_minutes) {
+        return Err(AppError::invalid_field(
+            "snapshot_interval_minutes",
+            tf(Msg::SnapshotIntervalRange, &[&MAX_SNAPSHOT_INTERVAL_MINUTES]),
+        ));
+    }
+
+    let retention = &settings.snapshot_retention;
+    if retention.hourly + retention.daily + retention.weekly == 0 {
+        return Err(AppError::invalid_field("snapshot_retention", t(Msg::RetentionEmpty)));
+    }
+
+    Ok(())
+}
+
+fn read_settings_file() -> AppResult<Settings> {
+    match fs::read_to_string(SETTINGS_FILE_PATH) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| AppError::parse(SETTINGS_FILE_PATH, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(AppError::read(SETTINGS_FILE_PATH, e)),
+    }
+}
+
+/// Applique les variables d'environnement à des réglages valides. Si le résultat ne l'est
+/// plus, elles sont ignorées en bloc, au chargement comme après une modification.
+fn with_env_overrides(settings: Settings) -> Settings {
+    let overridden = apply_env_overrides(settings.clone());
+    match validate(&overridden) {
+        Ok(()) => overridden,
+        Err(e) => {
+            eprintln!("⚠️ Variables PERSONAI_* ignorées, elles rendent les réglages invalides: {}", e);
+            settings
+        }
+    }
+}
+
+fn apply_env_overrides(mut settings: Settings) -> Settings {
+    env_override("PERSONAI_LOCALE", &mut settings.locale);
+    env_override("PERSONAI_MODEL", &mut settings.model);
+    env_override("PERSONAI_OLLAMA_PORT", &mut settings.ollama_port);
+    env_override("PERSONAI_PYTHON_PORT", &mut settings.python_port);
+    env_override("PERSONAI_GO_PORT", &mut settings.go_port);
+    env_override("PERSONAI_AUTO_START", &mut settings.auto_start_services);
+    env_override("PERSONAI_MAX_RESTARTS", &mut settings.max_restarts);
+    env_override("PERSONAI_HEALTH_INTERVAL_SECS", &mut settings.health_interval_secs);
+    env_override("PERSONAI_SNAPSHOT_INTERVAL_MINUTES", &mut settings.snapshot_interval_minutes);
+    env_override("PERSONAI_OPENAI_ENABLED", &mut settings.openai_server.enabled);
+    env_override("PERSONAI_OPENAI_PORT", &mut settings.openai_server.port);
+    env_override("PERSONAI_OPENAI_TOKEN", &mut settings.openai_server.token);
+    settings
+}
+
+fn env_override<T: FromStr>(name: &str, target: &mut T) {
+    let Ok(raw) = env::var(name) else {
+        return;
+    };
+
+    match raw.trim().parse() {
+        Ok(value) => *target = value,
+        Err(_) => eprintln!("⚠️ Valeur invalide ignorée pour {}: {}", name, raw),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RetentionPolicy;
+
+    fn rejected_field(settings: &Settings) -> String {
+        match validate(settings) {
+            Err(AppError::Validation { field: Some(field), .. }) => field,
+            other => panic!("réglages acceptés ou mauvaise erreur: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(validate(&Settings::default()).is_ok());
+    }
+
+    #[test]
+    fn ports_must_be_in_range_and_distinct() {
+        let mut settings = Settings { go_port: 80, ..Settings::default() };
+        assert_eq!(rejected_field(&settings), "ports");
+
+        settings.go_port = 65535;
+        assert_eq!(rejected_field(&settings), "ports");
+
+        settings.go_port = MAX_PORT;
+        assert!(validate(&settings).is_ok());
+
+        settings.go_port = settings.python_port;
+        assert_eq!(rejected_field(&settings), "ports");
+    }
+
+    #[test]
+    fn openai_server_needs_a_token_and_its_own_port() {
+        let mut settings = Settings::default();
+        settings.openai_server.enabled = true;
+        assert_eq!(rejected_field(&settings), "openai_server.token");
+
+        settings.openai_server.token = "secret".to_string();
+        settings.openai_server.port = settings.go_port;
+        assert_eq!(rejected_field(&settings), "ports");
+    }
+
+    #[test]
+    fn max_restarts_is_capped() {
+        let mut settings = Settings { max_restarts: MAX_RESTARTS, ..Settings::default() };
+        assert!(validate(&settings).is_ok());
+
+        settings.max_restarts = MAX_RESTARTS + 1;
+        assert_eq!(rejected_field(&settings), "max_restarts");
+    }
+
+    #[test]
+    fn intervals_and_retention_are_checked() {
+        let settings = Settings { health_interval_secs: 0, ..Settings::default() };
+        assert_eq!(rejected_field(&settings), "health_interval_secs");
+
+        let settings = Settings { snapshot_interval_minutes: 0, ..Settings::default() };
+        assert_eq!(rejected_field(&settings), "snapshot_interval_minutes");
+
+        let settings = Settings { snapshot_interval_minutes: u64::MAX, ..Settings::default() };
+        assert_eq!(rejected_field(&settings), "snapshot_interval_minutes");
+
+        let settings = Settings {
+            snapshot_retention: RetentionPolicy { hourly: 0, daily: 0, weekly: 0 },
+            ..Settings::default()
+        };
+        assert_eq!(rejected_field(&settings), "snapshot_retention");
+
+        let settings = Settings { model: " ".to_string(), ..Settings::default() };
+        assert_eq!(rejected_field(&settings), "model");
+    }
+
+    #[test]
+    fn invalid_env_overrides_are_ignored() {
+        // Seul test à toucher à cette variable : pas de course avec les autres.
+        env::set_var("PERSONAI_HEALTH_INTERVAL_SECS", "0");
+        let effective = with_env_overrides(Settings::default());
+        env::remove_var("PERSONAI_HEALTH_INTERVAL_SECS");
+
+        assert_eq!(effective.health_interval_secs, Settings::default().health_interval_secs);
+    }
+}
@@ -0,0 +1,516 @@
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils::{self, sha256_hex, DATA_DIR, SETTINGS_FILE_PATH, SNAPSHOTS_DIR};
+use crate::settings::SettingsStore;
+use crate::types::{Character, ChatMessage, Persona, RetentionPolicy, SnapshotDiff, SnapshotInfo};
+use crate::vault::{Vault, ENCRYPTED_EXTENSION};
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const CHARACTERS_FILE: &str = "characters.json";
+const PERSONAS_FILE: &str = "personas.json";
+const HISTORY_SUBDIR: &str = "history";
+const GROUP_HISTORY_SUBDIR: &str = "history/groups";
+const RELATIONSHIPS_SUBDIR: &str = "history/relationships";
+const SCENES_SUBDIR: &str = "scenes";
+
+/// Sous-dossiers de `data/` suivis en plus de la racine ; leurs fichiers sont protégés par le coffre.
+const TRACKED_SUBDIRS: [&str; 4] = [HISTORY_SUBDIR, GROUP_HISTORY_SUBDIR, RELATIONSHIPS_SUBDIR, SCENES_SUBDIR];
+
+/// Sérialise la prise de snapshot, l'élagage et les restaurations.
+static SNAPSHOT_LOCK: Mutex<()> = Mutex::new(());
+
+type BucketKey = fn(&DateTime<Local>) -> String;
+
+/// Un snapshot ne contient que la liste des fichiers (chemin relatif à `data/` → hash) ;
+/// le contenu est stocké une seule fois dans `snapshots/objects/<sha256>`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SnapshotManifest {
+    id: String,
+    created_at: String,
+    files: BTreeMap<String, String>,
+}
+
+/// Prend un snapshot à l'intervalle défini dans les réglages, relus à chaque tour.
+pub fn start_snapshot_service(settings: Arc<SettingsStore>) {
+    thread::spawn(move || loop {
+        let current = settings.get();
+
+        match take_snapshot() {
+            Ok(Some(id)) => println!("📸 Snapshot créé: {}", id),
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ Échec du snapshot: {}", e),
+        }
+
+        if let Err(e) = prune_snapshots(&current.snapshot_retention) {
+            eprintln!("❌ Échec de l'élagage des snapshots: {}", e);
+        }
+
+        thread::sleep(Duration::from_secs(current.snapshot_interval_minutes.saturating_mul(60)));
+    });
+}
+
+/// Crée un snapshot du dossier de données, sauf s'il est identique au plus récent.
+pub fn take_snapshot() -> AppResult<Option<String>> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    fs::create_dir_all(objects_dir()).map_err(|e| AppError::create_dir(objects_dir(), e))?;
+
+    let mut files = BTreeMap::new();
+    for (rel_path, path) in data_files() {
+        let data = fs::read(&path).map_err(|e| AppError::read(&path, e))?;
+        let hash = sha256_hex(&data);
+
+        let object = objects_dir().join(&hash);
+        if !object.exists() {
+            fs::write(&object, &data).map_err(|e| AppError::write(&object, e))?;
+        }
+
+        files.insert(rel_path, hash);
+    }
+
+    if let Some(latest) = read_manifests()?.first() {
+        if latest.files == files {
+            return Ok(None);
+        }
+    }
+
+    let now = Local::now();
+    let manifest = SnapshotManifest {
+        id: now.format("%Y%m%d-%H%M%S").to_string(),
+        created_at: now.to_rfc3339(),
+        files,
+    };
+
+    write_manifest(&manifest)?;
+    Ok(Some(manifest.id))
+}
+
+/// Chiffre, dans tous les snapshots, les copies des fichiers que protège le coffre : sans
+/// cela, l'activation laisserait leur version en clair dans `snapshots/objects`.
+pub fn seal_snapshots(vault: &Vault) -> AppResult<()> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut manifests = read_manifests()?;
+    // Un même objet (contenu + nom) n'est chiffré qu'une fois pour conserver la déduplication.
+    let mut sealed_objects: HashMap<(String, String), String> = HashMap::new();
+
+    for manifest in &mut manifests {
+        let protected: Vec<(String, String)> = manifest
+            .files
+            .iter()
+            .filter(|(rel_path, _)| is_protected(rel_path))
+            .map(|(rel_path, hash)| (rel_path.clone(), hash.clone()))
+            .collect();
+        if protected.is_empty() {
+            continue;
+        }
+
+        for (rel_path, hash) in protected {
+            manifest.files.remove(&rel_path);
+
+            let sealed_path = format!("{}.{}", rel_path, ENCRYPTED_EXTENSION);
+            if manifest.files.contains_key(&sealed_path) {
+                continue;
+            }
+
+            let name = rel_path.rsplit('/').next().unwrap_or(&rel_path).to_string();
+            let sealed_hash = match sealed_objects.get(&(hash.clone(), name.clone())) {
+                Some(sealed_hash) => sealed_hash.clone(),
+                None => {
+                    let object = objects_dir().join(&hash);
+                    let data = fs::read(&object).map_err(|e| AppError::read(&object, e))?;
+                    let sealed = vault.seal_bytes(&name, &data)?;
+                    let sealed_hash = sha256_hex(&sealed);
+
+                    let sealed_object = objects_dir().join(&sealed_hash);
+                    fs::write(&sealed_object, &sealed).map_err(|e| AppError::write(&sealed_object, e))?;
+                    sealed_objects.insert((hash, name), sealed_hash.clone());
+                    sealed_hash
+                }
+            };
+            manifest.files.insert(sealed_path, sealed_hash);
+        }
+
+        write_manifest(manifest)?;
+    }
+
+    remove_unreferenced_objects(&manifests);
+    Ok(())
+}
+
+/// Conserve le snapshot le plus récent de chacune des N dernières heures, jours et semaines
+/// de la politique, supprime les autres puis les objets qui ne sont plus référencés.
+pub fn prune_snapshots(policy: &RetentionPolicy) -> AppResult<()> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let manifests = read_manifests()?;
+    let keep = retained(&manifests, policy);
+
+    for manifest in manifests.iter().filter(|m| !keep.contains(m.id.as_str())) {
+        let _ = fs::remove_file(manifest_path(&manifest.id));
+    }
+
+    remove_unreferenced_objects(manifests.iter().filter(|m| keep.contains(m.id.as_str())));
+    Ok(())
+}
+
+/// Ids des snapshots à conserver ; `manifests` est trié du plus récent au plus ancien.
+fn retained<'a>(manifests: &'a [SnapshotManifest], policy: &RetentionPolicy) -> HashSet<&'a str> {
+    let dated: Vec<(&SnapshotManifest, DateTime<Local>)> = manifests
+        .iter()
+        .filter_map(|m| {
+            DateTime::parse_from_rfc3339(&m.created_at)
+                .ok()
+                .map(|d| (m, d.with_timezone(&Local)))
+        })
+        .collect();
+
+    let mut keep: HashSet<&str> = HashSet::new();
+    if let Some(latest) = manifests.first() {
+        keep.insert(&latest.id);
+    }
+
+    let buckets: [(usize, BucketKey); 3] = [
+        (policy.hourly, |d| d.format("%Y%m%d%H").to_string()),
+        (policy.daily, |d| d.format("%Y%m%d").to_string()),
+        (policy.weekly, |d| format!("{}-{}", d.iso_week().year(), d.iso_week().week())),
+    ];
+
+    for (count, bucket) in buckets {
+        let mut seen = HashSet::new();
+        for (manifest, date) in &dated {
+            if seen.len() >= count {
+                break;
+            }
+            if seen.insert(bucket(date)) {
+                keep.insert(&manifest.id);
+            }
+        }
+    }
+
+    keep
+}
+
+fn remove_unreferenced_objects<'a>(manifests: impl IntoIterator<Item = &'a SnapshotManifest>) {
+    let referenced: HashSet<&String> = manifests.into_iter().flat_map(|m| m.files.values()).collect();
+
+    if let Ok(entries) = fs::read_dir(objects_dir()) {
+        for entry in entries.flatten() {
+            if let Ok(name) = entry.file_name().into_string() {
+                if !referenced.contains(&name) {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
+}
+
+pub fn list_snapshots() -> AppResult<Vec<SnapshotInfo>> {
+    Ok(read_manifests()?
+        .into_iter()
+        .map(|m| SnapshotInfo {
+            histories: m.files.keys().filter(|p| is_history(p)).count(),
+            file_count: m.files.len(),
+            id: m.id,
+            created_at: m.created_at,
+        })
+        .collect())
+}
+
+/// Compare un snapshot à l'état actuel : « ajouté » signifie présent aujourd'hui
+/// mais absent du snapshot.
+pub fn diff_snapshot(vault: &Vault, snapshot_id: &str) -> AppResult<SnapshotDiff> {
+    let manifest = read_manifest(snapshot_id)?;
+
+    let mut current = BTreeMap::new();
+    for (rel_path, path) in data_files() {
+        let data = fs::read(&path).map_err(|e| AppError::read(&path, e))?;
+        current.insert(rel_path, sha256_hex(&data));
+    }
+
+    let mut diff = SnapshotDiff::default();
+
+    for (path, hash) in &current {
+        match manifest.files.get(path) {
+            None => diff.files_added.push(path.clone()),
+            Some(old) if old != hash => diff.files_modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.files_removed = manifest
+        .files
+        .keys()
+        .filter(|p| !current.contains_key(*p))
+        .cloned()
+        .collect();
+
+    let snapshot_characters: Vec<Character> = read_snapshot_json(vault, &manifest, CHARACTERS_FILE)?.unwrap_or_default();
+    let current_characters = file_utils::read_characters_file().unwrap_or_default();
+    (diff.characters_added, diff.characters_removed, diff.characters_modified) = diff_by_id(
+        &snapshot_characters,
+        &current_characters,
+        |c| &c.id,
+    );
+
+    let snapshot_personas: Vec<Persona> = read_snapshot_json(vault, &manifest, PERSONAS_FILE)?.unwrap_or_default();
+    let current_personas = file_utils::read_personas_file(vault).unwrap_or_default();
+    (diff.personas_added, diff.personas_removed, diff.personas_modified) = diff_by_id(
+        &snapshot_personas,
+        &current_personas,
+        |p| &p.id,
+    );
+
+    Ok(diff)
+}
+
+pub fn restore_character(vault: &Vault, snapshot_id: &str, character_id: &str) -> AppResult<()> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let manifest = read_manifest(snapshot_id)?;
+    let characters: Vec<Character> = read_snapshot_json(vault, &manifest, CHARACTERS_FILE)?.unwrap_or_default();
+
+    let character = characters
+        .into_iter()
+        .find(|c| c.id == character_id)
+        .ok_or_else(|| AppError::not_found(Entity::Character, character_id))?;
+
+    file_utils::update_character(character)
+}
+
+pub fn restore_chat(vault: &Vault, snapshot_id: &str, character_id: &str, persona_id: &str) -> AppResult<()> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let manifest = read_manifest(snapshot_id)?;
+    let rel_path = format!("{}/{}_{}.json", HISTORY_SUBDIR, character_id, persona_id);
+
+    let messages: Vec<ChatMessage> = read_snapshot_json(vault, &manifest, &rel_path)?
+        .ok_or_else(|| AppError::not_found(Entity::Chat, format!("{}_{}", character_id, persona_id)))?;
+
+    file_utils::write_chat_history(vault, character_id, persona_id, &messages)
+}
+
+fn diff_by_id<T: Serialize>(
+    before: &[T],
+    after: &[T],
+    id: fn(&T) -> &String,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut added = vec![];
+    let mut modified = vec![];
+
+    for item in after {
+        match before.iter().find(|b| id(b) == id(item)) {
+            None => added.push(id(item).clone()),
+            Some(old) if serde_json::to_value(old).ok() != serde_json::to_value(item).ok() => {
+                modified.push(id(item).clone())
+            }
+            Some(_) => {}
+        }
+    }
+
+    let removed = before
+        .iter()
+        .filter(|b| !after.iter().any(|a| id(a) == id(b)))
+        .map(|b| id(b).clone())
+        .collect();
+
+    (added, removed, modified)
+}
+
+/// Fichiers JSON du dossier de données (racine et `TRACKED_SUBDIRS`), chiffrés ou non, hors
+/// snapshots. Les réglages n'en font pas partie : ils portent le jeton de l'API OpenAI.
+fn data_files() -> Vec<(String, PathBuf)> {
+    let mut files = vec![];
+    let settings = Path::new(SETTINGS_FILE_PATH).file_name();
+
+    let dirs = std::iter::once(("", PathBuf::from(DATA_DIR)))
+        .chain(TRACKED_SUBDIRS.iter().map(|subdir| (*subdir, Path::new(DATA_DIR).join(subdir))));
+    for (prefix, dir) in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let tracked = path
+                .extension()
+                .map(|e| e == "json" || e == ENCRYPTED_EXTENSION)
+                .unwrap_or(false);
+            if !path.is_file() || !tracked || (prefix.is_empty() && path.file_name() == settings) {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                let rel_path = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                files.push((rel_path, path.clone()));
+            }
+        }
+    }
+
+    files
+}
+
+/// Lit `rel_path` dans le snapshot, ou sa version chiffrée si elle y figure.
+fn read_snapshot_json<T: serde::de::DeserializeOwned>(
+    vault: &Vault,
+    manifest: &SnapshotManifest,
+    rel_path: &str,
+) -> AppResult<Option<T>> {
+    let sealed_path = format!("{}.{}", rel_path, ENCRYPTED_EXTENSION);
+
+    let data = if let Some(hash) = manifest.files.get(&sealed_path) {
+        let object = objects_dir().join(hash);
+        let sealed = fs::read(&object).map_err(|e| AppError::read(&object, e))?;
+        let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        vault.open_bytes(name, &sealed)?
+    } else if let Some(hash) = manifest.files.get(rel_path) {
+        let object = objects_dir().join(hash);
+        fs::read(&object).map_err(|e| AppError::read(&object, e))?
+    } else {
+        return Ok(None);
+    };
+
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(|e| AppError::parse(rel_path, e))
+}
+
+/// Manifestes triés du plus récent au plus ancien.
+fn read_manifests() -> AppResult<Vec<SnapshotManifest>> {
+    let mut manifests = vec![];
+
+    if let Ok(entries) = fs::read_dir(SNAPSHOTS_DIR) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                let content = fs::read_to_string(&path).map_err(|e| AppError::read(&path, e))?;
+                let manifest: SnapshotManifest = serde_json::from_str(&content)
+                    .map_err(|e| AppError::parse(path.display().to_string(), e))?;
+                manifests.push(manifest);
+            }
+        }
+    }
+
+    manifests.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(manifests)
+}
+
+fn read_manifest(snapshot_id: &str) -> AppResult<SnapshotManifest> {
+    read_manifests()?
+        .into_iter()
+        .find(|m| m.id == snapshot_id)
+        .ok_or_else(|| AppError::not_found(Entity::Snapshot, snapshot_id))
+}
+
+fn write_manifest(manifest: &SnapshotManifest) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| AppError::parse(manifest.id.as_str(), e))?;
+    let path = manifest_path(&manifest.id);
+    fs::write(&path, content).map_err(|e| AppError::write(&path, e))
+}
+
+/// Fichiers en clair que le coffre chiffre (voir `file_utils::vault_protected_files`).
+fn is_protected(rel_path: &str) -> bool {
+    rel_path == PERSONAS_FILE || (parent_subdir(rel_path).is_some() && rel_path.ends_with(".json"))
+}
+
+/// Historique d'une conversation directe ou de groupe, chiffré ou non.
+fn is_history(rel_path: &str) -> bool {
+    matches!(parent_subdir(rel_path), Some(HISTORY_SUBDIR) | Some(GROUP_HISTORY_SUBDIR))
+}
+
+fn parent_subdir(rel_path: &str) -> Option<&str> {
+    rel_path
+        .rsplit_once('/')
+        .map(|(dir, _)| dir)
+        .filter(|dir| TRACKED_SUBDIRS.contains(dir))
+}
+
+fn manifest_path(snapshot_id: &str) -> PathBuf {
+    Path::new(SNAPSHOTS_DIR).join(format!("{}.json", snapshot_id))
+}
+
+fn objects_dir() -> PathBuf {
+    Path::new(SNAPSHOTS_DIR).join("objects")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Snapshots pris toutes les `step_minutes` en remontant depuis `newest`, du plus récent au plus ancien.
+    fn manifests(newest: DateTime<Local>, count: i64, step_minutes: i64) -> Vec<SnapshotManifest> {
+        (0..count)
+            .map(|i| {
+                let date = newest - chrono::Duration::minutes(i * step_minutes);
+                SnapshotManifest {
+                    id: date.format("%Y%m%d-%H%M%S").to_string(),
+                    created_at: date.to_rfc3339(),
+                    files: BTreeMap::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn policy(hourly: usize, daily: usize, weekly: usize) -> RetentionPolicy {
+        RetentionPolicy { hourly, daily, weekly }
+    }
+
+    #[test]
+    fn keeps_the_latest_snapshot_of_each_recent_hour() {
+        let newest = Local.with_ymd_and_hms(2024, 5, 15, 12, 45, 0).unwrap();
+        let all = manifests(newest, 16, 15);
+
+        let keep = retained(&all, &policy(3, 0, 0));
+
+        // 12h45, puis le plus récent de 11h et de 10h.
+        assert_eq!(keep.len(), 3);
+        assert!(keep.contains(all[0].id.as_str()));
+        assert!(keep.contains("20240515-114500"));
+        assert!(keep.contains("20240515-104500"));
+    }
+
+    #[test]
+    fn daily_and_weekly_buckets_add_older_snapshots() {
+        let newest = Local.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+        let all = manifests(newest, 30, 24 * 60);
+
+        assert_eq!(retained(&all, &policy(0, 7, 0)).len(), 7);
+        // Les sept jours retenus tombent dans deux semaines ISO ; les quatre semaines en ajoutent deux.
+        assert_eq!(retained(&all, &policy(0, 7, 4)).len(), 9);
+    }
+
+    #[test]
+    fn the_latest_snapshot_is_always_kept() {
+        let newest = Local.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+        let all = manifests(newest, 5, 60);
+
+        let keep = retained(&all, &policy(0, 0, 0));
+        assert_eq!(keep, HashSet::from([all[0].id.as_str()]));
+        assert!(retained(&[], &policy(1, 1, 1)).is_empty());
+    }
+
+    #[test]
+    fn protected_files_match_the_vault() {
+        assert!(is_protected("personas.json"));
+        assert!(is_protected("history/alice_1_bob_2.json"));
+        assert!(is_protected("history/groups/group_1.json"));
+        assert!(is_protected("history/relationships/alice_1_bob_2.json"));
+        assert!(is_protected("scenes/group-group_1.json"));
+        assert!(!is_protected("history/alice_1_bob_2.json.enc"));
+        assert!(!is_protected("characters.json"));
+        assert!(!is_protected("groups.json"));
+
+        assert!(is_history("history/groups/group_1.json.enc"));
+        assert!(!is_history("history/relationships/alice_1_bob_2.json"));
+    }
+}
@@ -0,0 +1,600 @@
+use crate::error::{AppError, AppResult};
+use crate::i18n::{t, tf, Msg};
+use crate::http::HttpClient;
+use crate::logs::ServiceLogs;
+use crate::settings::SettingsStore;
+use crate::services::{
+    base_url, find_free_port, identify_port_occupant, port_in_use, probe_ready, spawn_service,
+    terminate_child, upstream, PortOccupant,
+};
+use crate::types::{
+    AppMode, LogLevel, LogLine, ServiceEvent, ServiceIssue, ServiceKind, ServiceStartError, ServiceState, ServiceStatus, SystemStatus,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Child;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: u64 = 60;
+/// Au-delà de cette durée sans crash, le compteur de redémarrages est remis à zéro.
+pub const STABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// Ordre de démarrage : Python appelle Ollama, Go appelle Python.
+pub const STARTUP_ORDER: [ServiceKind; 3] = [ServiceKind::Ollama, ServiceKind::Python, ServiceKind::Go];
+
+pub const SERVICE_READY_EVENT: &str = "service-ready";
+pub const SERVICE_FAILED_EVENT: &str = "service-failed";
+/// Le port par défaut d'un service est occupé par un programme tiers.
+pub const SERVICE_CONFLICT_EVENT: &str = "service-conflict";
+/// Émis quand le mode de l'application (complet, démarrage, dégradé) ou la liste des problèmes change.
+pub const SYSTEM_STATUS_EVENT: &str = "system-status";
+
+struct ManagedService {
+    child: Option<Child>,
+    state: ServiceState,
+    port: u16,
+    /// Instance trouvée déjà en marche au démarrage : on ne la possède pas.
+    adopted: bool,
+    /// `false` après un arrêt explicite : le moniteur ne relance alors pas le service.
+    wanted: bool,
+    restarts: u32,
+    started_at: Option<Instant>,
+    next_restart: Option<Instant>,
+    last_exit: Option<String>,
+    last_error: Option<ServiceStartError>,
+}
+
+impl ManagedService {
+    fn new(port: u16) -> Self {
+        Self {
+            child: None,
+            state: ServiceState::Stopped,
+            port,
+            adopted: false,
+            wanted: false,
+            restarts: 0,
+            started_at: None,
+            next_restart: None,
+            last_exit: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Possède les processus des services annexes (Ollama, Python, Go) et les relance
+/// avec un délai exponentiel lorsqu'ils s'arrêtent sans qu'on le leur ait demandé.
+pub struct Supervisor {
+    services: Mutex<HashMap<ServiceKind, ManagedService>>,
+    settings: Arc<SettingsStore>,
+    http: Arc<HttpClient>,
+    app: OnceLock<AppHandle>,
+    logs: Arc<ServiceLogs>,
+    /// Dernier état publié via `system-status`, pour n'émettre que les changements.
+    published: Mutex<Option<SystemStatus>>,
+}
+
+impl Supervisor {
+    pub fn new(settings: Arc<SettingsStore>, http: Arc<HttpClient>) -> Self {
+        let current = settings.get();
+        Self {
+            services: Mutex::new(
+                STARTUP_ORDER
+                    .iter()
+                    .map(|kind| (*kind, ManagedService::new(current.port(*kind))))
+                    .collect(),
+            ),
+            settings,
+            http,
+            app: OnceLock::new(),
+            logs: Arc::new(ServiceLogs::new()),
+            published: Mutex::new(None),
+        }
+    }
+
+    /// Branche le superviseur sur l'application pour qu'il puisse émettre ses événements.
+    pub fn attach(&self, app: AppHandle) {
+        self.logs.attach(app.clone());
+        let _ = self.app.set(app);
+    }
+
+    pub fn spawn_monitor(self: &Arc<Self>) {
+        let supervisor = Arc::clone(self);
+        thread::spawn(move || loop {
+            supervisor.check_children();
+            thread::sleep(MONITOR_INTERVAL);
+        });
+    }
+
+    /// Démarre les services qui ne tournent pas encore, dans l'ordre de dépendance, en
+    /// attendant que chacun soit prêt avant de lancer le suivant. Bloquant : à appeler
+    /// depuis un thread dédié. Renvoie `false` si tout tournait déjà. En cas d'échec,
+    /// l'application reste utilisable en mode dégradé.
+    pub fn start_all(&self) -> Result<bool, Vec<ServiceStartError>> {
+        println!("🚀 Démarrage de tous les services...");
+        let mut started_any = false;
+        let mut errors = vec![];
+
+        for kind in STARTUP_ORDER {
+            match self.start(kind) {
+                Ok(started) => started_any |= started,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            }
+            if self.state(kind) == ServiceState::Starting {
+                if let Err(e) = self.await_ready(kind) {
+                    errors.push(e);
+                }
+            }
+        }
+
+        self.publish_status();
+        if !errors.is_empty() {
+            eprintln!("⚠️ Mode dégradé : le chat est indisponible");
+            return Err(errors);
+        }
+
+        println!("✅ Tous les services sont prêts");
+        Ok(started_any)
+    }
+
+    /// Démarre un service s'il ne tourne pas déjà, sans attendre qu'il soit prêt.
+    /// Si son port est pris, adopte l'instance existante quand c'est la nôtre, sinon se
+    /// replie sur un port libre. Renvoie `true` si un processus a été lancé.
+    pub fn start(&self, kind: ServiceKind) -> Result<bool, ServiceStartError> {
+        if self.is_running(kind) {
+            return Ok(false);
+        }
+
+        let preferred = self.settings.get().port(kind);
+        let port = match identify_port_occupant(self.http.probe(), kind, preferred) {
+            PortOccupant::Free => preferred,
+            PortOccupant::Ours => {
+                self.adopt(kind, preferred);
+                return Ok(false);
+            }
+            PortOccupant::Foreign => self.relocate(kind, preferred)?,
+        };
+
+        let upstream_url = upstream(kind).map(|dep| self.base_url(dep));
+
+        let mut services = self.lock();
+        let service = services.get_mut(&kind).expect("service inconnu");
+        if service.child.is_some() || service.adopted {
+            return Ok(false);
+        }
+
+        service.port = port;
+        service.wanted = true;
+        service.restarts = 0;
+        spawn_into(kind, service, upstream_url, &self.logs)?;
+        Ok(true)
+    }
+
+    pub fn logs(&self, kind: ServiceKind, since: Option<u64>, level: Option<LogLevel>) -> Vec<LogLine> {
+        self.logs.lines(kind, since, level)
+    }
+
+    /// Adresse HTTP à laquelle joindre le service, sur son port effectif.
+    pub fn base_url(&self, kind: ServiceKind) -> String {
+        base_url(self.lock()[&kind].port)
+    }
+
+    /// Démarre un service et surveille sa disponibilité en arrière-plan.
+    pub fn start_in_background(self: &Arc<Self>, kind: ServiceKind) -> Result<(), ServiceStartError> {
+        let started = self.start(kind);
+        self.publish_status();
+        if started? {
+            self.probe_in_background(kind);
+        }
+        Ok(())
+    }
+
+    /// Arrête un service lancé par l'application ; une instance adoptée est seulement
+    /// relâchée, sans être tuée.
+    pub fn stop(&self, kind: ServiceKind) {
+        let child = {
+            let mut services = self.lock();
+            let service = services.get_mut(&kind).expect("service inconnu");
+            service.wanted = false;
+            service.adopted = false;
+            service.next_restart = None;
+            service.started_at = None;
+            service.state = ServiceState::Stopped;
+            service.child.take()
+        };
+
+        if let Some(mut child) = child {
+            println!("🛑 Arrêt de {}...", kind.label());
+            terminate_child(&mut child);
+        }
+        self.publish_status();
+    }
+
+    pub fn restart(self: &Arc<Self>, kind: ServiceKind) -> AppResult<()> {
+        if self.lock()[&kind].adopted {
+            return Err(AppError::conflict(tf(Msg::AdoptedCannotRestart, &[&kind.label()])));
+        }
+        self.stop(kind);
+        Ok(self.start_in_background(kind)?)
+    }
+
+    pub fn stop_all(&self) {
+        for kind in STARTUP_ORDER.iter().rev() {
+            self.stop(*kind);
+        }
+    }
+
+    pub fn status(&self) -> Vec<ServiceStatus> {
+        let services = self.lock();
+
+        STARTUP_ORDER
+            .iter()
+            .map(|kind| {
+                let service = &services[kind];
+                ServiceStatus {
+                    service: *kind,
+                    state: service.state,
+                    port: service.port,
+                    adopted: service.adopted,
+                    pid: service.child.as_ref().map(|c| c.id()),
+                    restarts: service.restarts,
+                    uptime_secs: service.started_at.map(|t| t.elapsed().as_secs()),
+                    last_exit: service.last_exit.clone(),
+                    last_error: service.last_error.as_ref().map(|e| e.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Mode de fonctionnement global et, pour chaque service indisponible, la cause et un
+    /// conseil de résolution.
+    pub fn system_status(&self) -> SystemStatus {
+        let services = self.lock();
+
+        let issues: Vec<ServiceIssue> = STARTUP_ORDER
+            .iter()
+            .filter_map(|kind| {
+                let service = &services[kind];
+                if matches!(service.state, ServiceState::Running | ServiceState::Starting) {
+                    return None;
+                }
+                let (message, hint) = match &service.last_error {
+                    Some(e) => (e.to_string(), e.hint()),
+                    None => (
+                        tf(Msg::ServiceStopped, &[&kind.label()]),
+                        t(Msg::HintStartService).to_string(),
+                    ),
+                };
+                Some(ServiceIssue {
+                    service: *kind,
+                    state: service.state,
+                    message,
+                    hint,
+                    error: service.last_error.clone(),
+                })
+            })
+            .collect();
+
+        let chat_available = STARTUP_ORDER.iter().all(|kind| services[kind].state == ServiceState::Running);
+        let mode = if chat_available {
+            AppMode::Full
+        } else if issues.is_empty() {
+            AppMode::Starting
+        } else {
+            AppMode::Degraded
+        };
+
+        SystemStatus {
+            mode,
+            chat_available,
+            issues,
+        }
+    }
+
+    fn publish_status(&self) {
+        let status = self.system_status();
+        {
+            let mut published = self.published.lock().unwrap_or_else(|e| e.into_inner());
+            if published.as_ref() == Some(&status) {
+                return;
+            }
+            *published = Some(status.clone());
+        }
+        self.emit(SYSTEM_STATUS_EVENT, status);
+    }
+
+    fn state(&self, kind: ServiceKind) -> ServiceState {
+        self.lock()[&kind].state
+    }
+
+    pub fn is_available(&self, kind: ServiceKind) -> bool {
+        self.state(kind) == ServiceState::Running
+    }
+
+    fn is_running(&self, kind: ServiceKind) -> bool {
+        let services = self.lock();
+        services[&kind].child.is_some() || services[&kind].adopted
+    }
+
+    /// Utilise l'instance déjà en marche sur `port` au lieu d'en lancer une seconde.
+    fn adopt(&self, kind: ServiceKind, port: u16) {
+        {
+            let mut services = self.lock();
+            let service = services.get_mut(&kind).expect("service inconnu");
+            service.adopted = true;
+            service.port = port;
+            service.wanted = false;
+            service.state = ServiceState::Running;
+            service.started_at = Some(Instant::now());
+            service.last_error = None;
+        }
+
+        println!("🔗 {} tourne déjà sur le port {} : instance adoptée", kind.label(), port);
+        self.logs.record(kind, LogLevel::Info, &tf(Msg::LogInstanceAdopted, &[&port]));
+        self.emit(SERVICE_READY_EVENT, ServiceEvent {
+            service: kind,
+            state: ServiceState::Running,
+            port,
+            elapsed_ms: Some(0),
+            error: None,
+        });
+        self.publish_status();
+    }
+
+    /// Cherche un port libre quand `preferred` est occupé par un programme tiers, et
+    /// signale le conflit à l'interface dans tous les cas.
+    fn relocate(&self, kind: ServiceKind, preferred: u16) -> Result<u16, ServiceStartError> {
+        let found = preferred.checked_add(1).and_then(find_free_port);
+        let (state, message) = match found {
+            Some(port) => (
+                ServiceState::Starting,
+                tf(Msg::PortTakenRelocated, &[&preferred, &kind.label(), &port]),
+            ),
+            None => (
+                ServiceState::Failed,
+                tf(Msg::PortTakenNoFallback, &[&preferred, &kind.label()]),
+            ),
+        };
+
+        eprintln!("⚠️ {}", message);
+        self.logs.record(kind, LogLevel::Warn, &message);
+        let error = ServiceStartError::PortUnavailable {
+            service: kind,
+            port: preferred,
+        };
+        if found.is_none() {
+            let mut services = self.lock();
+            let service = services.get_mut(&kind).expect("service inconnu");
+            service.state = state;
+            service.last_error = Some(error.clone());
+        }
+
+        self.emit(SERVICE_CONFLICT_EVENT, ServiceEvent {
+            service: kind,
+            state,
+            port: found.unwrap_or(preferred),
+            elapsed_ms: None,
+            error: Some(message),
+        });
+
+        found.ok_or(error)
+    }
+
+    /// Sonde le service jusqu'à ce qu'il réponde, met à jour son état et émet
+    /// `service-ready` ou `service-failed`.
+    fn await_ready(&self, kind: ServiceKind) -> Result<(), ServiceStartError> {
+        let port = self.lock()[&kind].port;
+        match probe_ready(self.http.probe(), kind, port, || self.state(kind) == ServiceState::Starting) {
+            Ok(elapsed) => {
+                if let Some(service) = self.lock().get_mut(&kind) {
+                    if service.state == ServiceState::Starting {
+                        service.state = ServiceState::Running;
+                        service.last_error = None;
+                    }
+                }
+                println!("✅ {} prêt en {} ms", kind.label(), elapsed.as_millis());
+                self.emit(SERVICE_READY_EVENT, ServiceEvent {
+                    service: kind,
+                    state: ServiceState::Running,
+                    port,
+                    elapsed_ms: Some(elapsed.as_millis() as u64),
+                    error: None,
+                });
+                self.publish_status();
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                self.logs.record(kind, LogLevel::Error, &e.to_string());
+                let state = {
+                    let mut services = self.lock();
+                    let service = services.get_mut(&kind).expect("service inconnu");
+                    if service.state == ServiceState::Starting {
+                        service.state = ServiceState::Failed;
+                    }
+                    service.last_error = Some(e.clone());
+                    service.state
+                };
+                self.emit(SERVICE_FAILED_EVENT, ServiceEvent {
+                    service: kind,
+                    state,
+                    port,
+                    elapsed_ms: None,
+                    error: Some(e.to_string()),
+                });
+                self.publish_status();
+                Err(e)
+            }
+        }
+    }
+
+    fn probe_in_background(self: &Arc<Self>, kind: ServiceKind) {
+        let supervisor = Arc::clone(self);
+        thread::spawn(move || {
+            let _ = supervisor.await_ready(kind);
+        });
+    }
+
+    /// Relance les processus arrêtés. Une instance adoptée n'a pas de processus à
+    /// surveiller : si son port ne répond plus, on la remplace par la nôtre.
+    fn check_children(self: &Arc<Self>) {
+        let mut respawned = vec![];
+
+        let adopted: Vec<(ServiceKind, u16)> = self
+            .lock()
+            .iter()
+            .filter(|(_, s)| s.adopted)
+            .map(|(kind, s)| (*kind, s.port))
+            .collect();
+        let gone: Vec<ServiceKind> = adopted
+            .into_iter()
+            .filter(|(_, port)| !port_in_use(*port))
+            .map(|(kind, _)| kind)
+            .collect();
+
+        {
+            let mut services = self.lock();
+            let now = Instant::now();
+            let max_restarts = self.settings.get().max_restarts;
+            let ports: HashMap<ServiceKind, u16> = services.iter().map(|(kind, s)| (*kind, s.port)).collect();
+
+            for (kind, service) in services.iter_mut() {
+                if service.adopted && gone.contains(kind) {
+                    let status = t(Msg::AdoptedInstanceGone).to_string();
+                    eprintln!("❌ {}", tf(Msg::Crashed, &[&kind.label(), &status]));
+                    self.logs.record(*kind, LogLevel::Error, &status);
+                    service.adopted = false;
+                    service.wanted = true;
+                    service.restarts = 0;
+                    service.started_at = None;
+                    service.last_exit = Some(status.clone());
+                    service.last_error = Some(ServiceStartError::Crashed {
+                        service: *kind,
+                        status,
+                    });
+                    schedule_restart(*kind, service, now, max_restarts);
+                } else if let Some(child) = service.child.as_mut() {
+                    match child.try_wait() {
+                        Ok(None) => {
+                            if service.restarts > 0 && service.started_at.map(|t| t.elapsed() > STABLE_AFTER).unwrap_or(false) {
+                                service.restarts = 0;
+                            }
+                        }
+                        Ok(Some(status)) => {
+                            eprintln!("❌ {} s'est arrêté ({})", kind.label(), status);
+                            self.logs.record(*kind, LogLevel::Error, &tf(Msg::LogUnexpectedExit, &[&status]));
+                            service.last_exit = Some(status.to_string());
+                            service.last_error = Some(ServiceStartError::Crashed {
+                                service: *kind,
+                                status: status.to_string(),
+                            });
+                            service.child = None;
+                            service.started_at = None;
+                            schedule_restart(*kind, service, now, max_restarts);
+                        }
+                        Err(e) => eprintln!("❌ Impossible de surveiller {}: {}", kind.label(), e),
+                    }
+                } else if service.wanted && service.next_restart.map(|t| now >= t).unwrap_or(false) {
+                    service.next_restart = None;
+                    println!("🔁 Redémarrage de {} (tentative {})", kind.label(), service.restarts);
+                    let upstream_url = upstream(*kind).map(|dep| base_url(ports[&dep]));
+                    match spawn_into(*kind, service, upstream_url, &self.logs) {
+                        Ok(()) => respawned.push(*kind),
+                        Err(e) => {
+                            eprintln!("❌ {}", e);
+                            schedule_restart(*kind, service, now, max_restarts);
+                        }
+                    }
+                }
+            }
+        }
+
+        for kind in respawned {
+            self.probe_in_background(kind);
+        }
+        self.publish_status();
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(app) = self.app.get() {
+            let _ = app.emit(event, payload);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<ServiceKind, ManagedService>> {
+        self.services.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn spawn_into(
+    kind: ServiceKind,
+    service: &mut ManagedService,
+    upstream_url: Option<String>,
+    logs: &Arc<ServiceLogs>,
+) -> Result<(), ServiceStartError> {
+    match spawn_service(kind, service.port, upstream_url) {
+        Ok(mut child) => {
+            logs.capture(kind, &mut child);
+            logs.record(kind, LogLevel::Info, &tf(Msg::LogProcessLaunched, &[&child.id(), &service.port]));
+            service.child = Some(child);
+            service.state = ServiceState::Starting;
+            service.started_at = Some(Instant::now());
+            Ok(())
+        }
+        Err(e) => {
+            logs.record(kind, LogLevel::Error, &e.to_string());
+            service.state = ServiceState::Failed;
+            service.last_error = Some(e.clone());
+            Err(e)
+        }
+    }
+}
+
+fn schedule_restart(kind: ServiceKind, service: &mut ManagedService, now: Instant, max_restarts: u32) {
+    if !service.wanted {
+        service.state = ServiceState::Stopped;
+        return;
+    }
+
+    if service.restarts >= max_restarts {
+        eprintln!("❌ {} abandonné après {} redémarrages", kind.label(), service.restarts);
+        service.state = ServiceState::Failed;
+        service.wanted = false;
+        return;
+    }
+
+    service.restarts += 1;
+    service.state = ServiceState::Backoff;
+    service.next_restart = Some(now + Duration::from_secs(backoff_secs(service.restarts - 1)));
+}
+
+/// Délai avant la relance suivante : 1, 2, 4... secondes, plafonné à `MAX_BACKOFF`.
+fn backoff_secs(restarts: u32) -> u64 {
+    1u64.checked_shl(restarts).unwrap_or(u64::MAX).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_then_caps() {
+        assert_eq!(backoff_secs(0), 1);
+        assert_eq!(backoff_secs(1), 2);
+        assert_eq!(backoff_secs(5), 32);
+        assert_eq!(backoff_secs(6), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn backoff_does_not_overflow_with_many_restarts() {
+        assert_eq!(backoff_secs(63), MAX_BACKOFF);
+        assert_eq!(backoff_secs(64), MAX_BACKOFF);
+        assert_eq!(backoff_secs(u32::MAX), MAX_BACKOFF);
+    }
+}
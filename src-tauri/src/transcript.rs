@@ -0,0 +1,307 @@
+use crate::error::{AppError, AppResult, Entity};
+use crate::file_utils::{public_asset_path, read_characters_file, read_chat_history, read_personas_file};
+use crate::i18n::{t, Msg};
+use crate::scene::NARRATOR_ROLE;
+use crate::types::{ChatMessage, TranscriptFormat};
+use crate::vault::Vault;
+use base64::Engine;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+struct Speaker {
+    name: String,
+    img: String,
+}
+
+struct Transcript {
+    character: Speaker,
+    persona: Speaker,
+    narrator: Speaker,
+    definition: String,
+    messages: Vec<ChatMessage>,
+}
+
+impl Transcript {
+    fn speaker(&self, message: &ChatMessage) -> &Speaker {
+        match message.role.as_str() {
+            "user" => &self.persona,
+            NARRATOR_ROLE => &self.narrator,
+            _ => &self.character,
+        }
+    }
+}
+
+pub fn export_transcript(
+    vault: &Vault,
+    character_id: &str,
+    persona_id: &str,
+    format: TranscriptFormat,
+    dest: &Path,
+) -> AppResult<()> {
+    let transcript = load_transcript(vault, character_id, persona_id)?;
+
+    let rendered = match format {
+        TranscriptFormat::Markdown => render_markdown(&transcript),
+        TranscriptFormat::Html => render_html(&transcript),
+        TranscriptFormat::Text => render_text(&transcript),
+        TranscriptFormat::Jsonl => render_jsonl(&transcript)?,
+    };
+
+    fs::write(dest, rendered).map_err(|e| AppError::write(dest, e))
+}
+
+fn load_transcript(vault: &Vault, character_id: &str, persona_id: &str) -> AppResult<Transcript> {
+    let messages = read_chat_history(vault, character_id, persona_id)?;
+    if messages.is_empty() {
+        return Err(AppError::not_found(Entity::Chat, format!("{}_{}", character_id, persona_id)));
+    }
+
+    let (character, definition) = match read_characters_file()?.into_iter().find(|c| c.id == character_id) {
+        Some(c) => (Speaker { name: c.name, img: c.img }, c.definition),
+        None => (Speaker { name: character_id.to_string(), img: String::new() }, String::new()),
+    };
+
+    let persona = match read_personas_file(vault)?.into_iter().find(|p| p.id == persona_id) {
+        Some(p) => Speaker { name: p.display_name, img: p.img },
+        None => Speaker { name: persona_id.to_string(), img: String::new() },
+    };
+
+    Ok(Transcript {
+        character,
+        persona,
+        narrator: Speaker { name: t(Msg::Narrator).to_string(), img: String::new() },
+        definition,
+        messages,
+    })
+}
+
+fn render_markdown(transcript: &Transcript) -> String {
+    let mut out = format!(
+        "# {} & {}\n\n",
+        transcript.character.name, transcript.persona.name
+    );
+
+    // Comme pour le HTML, les avatars sont intégrés : le fichier exporté ne dépend pas de l'application.
+    for speaker in [&transcript.character, &transcript.persona] {
+        if let Some(uri) = avatar_data_uri(&speaker.img) {
+            out.push_str(&format!("![{}]({})\n", speaker.name, uri));
+        }
+    }
+    out.push('\n');
+
+    for message in &transcript.messages {
+        out.push_str(&format!("**{}**", transcript.speaker(message).name));
+        if let Some(ts) = format_timestamp(message.timestamp.as_deref()) {
+            out.push_str(&format!(" · _{}_", ts));
+        }
+        out.push_str("\n\n");
+        out.push_str(message.content.trim());
+        out.push_str("\n\n---\n\n");
+    }
+
+    out
+}
+
+fn render_html(transcript: &Transcript) -> String {
+    let title = format!(
+        "{} &amp; {}",
+        escape_html(&transcript.character.name),
+        escape_html(&transcript.persona.name)
+    );
+
+    let mut body = String::new();
+    for message in &transcript.messages {
+        let speaker = transcript.speaker(message);
+        let avatar = avatar_data_uri(&speaker.img)
+            .map(|uri| format!("<img class=\"avatar\" src=\"{}\" alt=\"\">", uri))
+            .unwrap_or_default();
+        let timestamp = format_timestamp(message.timestamp.as_deref())
+            .map(|ts| format!("<time>{}</time>", ts))
+            .unwrap_or_default();
+
+        body.push_str(&format!(
+            "<div class=\"message {}\">{}<div class=\"bubble\"><div class=\"meta\"><strong>{}</strong>{}</div><p>{}</p></div></div>\n",
+            if message.role == "user" { "user" } else { "assistant" },
+            avatar,
+            escape_html(&speaker.name),
+            timestamp,
+            render_actions_html(message.content.trim()),
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 720px; margin: 2rem auto; background: #f5f5f7; color: #222; }}
+.message {{ display: flex; gap: 0.75rem; margin-bottom: 1rem; }}
+.message.user {{ flex-direction: row-reverse; }}
+.avatar {{ width: 48px; height: 48px; border-radius: 50%; object-fit: cover; }}
+.bubble {{ background: #fff; border-radius: 12px; padding: 0.75rem 1rem; max-width: 80%; }}
+.user .bubble {{ background: #dbeafe; }}
+.meta {{ font-size: 0.85rem; margin-bottom: 0.25rem; }}
+.meta time {{ color: #888; margin-left: 0.5rem; }}
+p {{ margin: 0; white-space: pre-wrap; }}
+em {{ color: #666; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}</body>
+</html>
+"#
+    )
+}
+
+fn render_text(transcript: &Transcript) -> String {
+    let mut out = format!(
+        "{} & {}\n\n",
+        transcript.character.name, transcript.persona.name
+    );
+
+    for message in &transcript.messages {
+        match format_timestamp(message.timestamp.as_deref()) {
+            Some(ts) => out.push_str(&format!("[{}] {}:\n", ts, transcript.speaker(message).name)),
+            None => out.push_str(&format!("{}:\n", transcript.speaker(message).name)),
+        }
+        out.push_str(message.content.trim());
+        out.push_str("\n\n");
+    }
+
+    out
+}
+
+/// Une ligne par conversation au format `messages` des jeux de données de fine-tuning.
+fn render_jsonl(transcript: &Transcript) -> AppResult<String> {
+    let mut messages = vec![json!({
+        "role": "system",
+        "content": format!("You are {}. {}", transcript.character.name, transcript.definition).trim().to_string(),
+    })];
+
+    messages.extend(transcript.messages.iter().map(|m| {
+        json!({
+            "role": if m.role == "user" { "user" } else { "assistant" },
+            "content": m.content.trim(),
+        })
+    }));
+
+    let line = serde_json::to_string(&json!({ "messages": messages }))
+        .map_err(|e| AppError::parse("transcript JSONL", e))?;
+
+    Ok(format!("{}\n", line))
+}
+
+/// Les réponses encadrent les actions par des astérisques (`*il sourit*`) : on les rend en italique.
+fn render_actions_html(content: &str) -> String {
+    let escaped = escape_html(content);
+    let mut out = String::with_capacity(escaped.len());
+    let mut parts = escaped.split('*').peekable();
+    let mut in_action = false;
+
+    while let Some(part) = parts.next() {
+        out.push_str(part);
+        if parts.peek().is_none() {
+            break;
+        }
+        out.push_str(if in_action { "</em>" } else { "<em>" });
+        in_action = !in_action;
+    }
+
+    if in_action {
+        out.push_str("</em>");
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn avatar_data_uri(img: &str) -> Option<String> {
+    if img.is_empty() {
+        return None;
+    }
+
+    let path = public_asset_path(img);
+    let mime = match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+    let data = fs::read(&path).ok()?;
+
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(data)
+    ))
+}
+
+/// Accepte le RFC 3339 ainsi que les horodatages sans fuseau écrits par le service Python.
+fn format_timestamp(timestamp: Option<&str>) -> Option<String> {
+    let ts = timestamp?;
+
+    let datetime = chrono::DateTime::parse_from_rfc3339(ts)
+        .map(|d| d.naive_local())
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()?;
+
+    Some(datetime.format("%Y-%m-%d %H:%M").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speaker(name: &str, img: &str) -> Speaker {
+        Speaker {
+            name: name.to_string(),
+            img: img.to_string(),
+        }
+    }
+
+    #[test]
+    fn actions_become_italics() {
+        assert_eq!(render_actions_html("*sourit* Bonjour"), "<em>sourit</em> Bonjour");
+        assert_eq!(render_actions_html("Salut *rit"), "Salut <em>rit</em>");
+        assert_eq!(render_actions_html("sans action"), "sans action");
+    }
+
+    #[test]
+    fn actions_are_escaped_before_formatting() {
+        assert_eq!(
+            render_actions_html("*<b>* & \"ok\""),
+            "<em>&lt;b&gt;</em> &amp; &quot;ok&quot;"
+        );
+    }
+
+    #[test]
+    fn markdown_never_links_to_app_assets() {
+        let transcript = Transcript {
+            character: speaker("Alice", "/assets/characters/missing.png"),
+            persona: speaker("Bob", ""),
+            narrator: speaker("Narrateur", ""),
+            definition: String::new(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: "Bonjour".to_string(),
+                timestamp: None,
+                speaker_id: None,
+            }],
+        };
+
+        let markdown = render_markdown(&transcript);
+        assert!(!markdown.contains("/assets/"));
+        assert!(markdown.contains("**Bob**\n\nBonjour"));
+    }
+}
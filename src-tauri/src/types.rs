@@ -1,6 +1,11 @@
+use crate::health::HealthMonitor;
+use crate::http::HttpClient;
+use crate::i18n::{t, tf, Msg};
+use crate::settings::SettingsStore;
+use crate::supervisor::Supervisor;
+use crate::vault::Vault;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,9 +38,54 @@ pub struct Character {
     pub definition: String,
     pub tags: HashMap<String, Vec<String>>,
     pub img: String,
+    /// Autres premiers messages possibles, en plus de `greeting`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_greetings: Vec<String>,
+    /// Échanges modèles qui montrent au modèle la voix du personnage.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub example_dialogues: Vec<ExampleDialogue>,
+    /// Absente, le personnage garde le comportement par défaut du modèle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub personality: Option<Personality>,
+    /// Persona proposée d'office quand on ouvre une conversation avec ce personnage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_persona_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExampleDialogue {
+    pub user: String,
+    pub character: String,
+}
+
+/// Curseurs de personnalité, de 0 à 100 (50 : neutre), traduits en consignes et en
+/// réglages de génération au moment du chat.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Personality {
+    pub warmth: u8,
+    pub verbosity: u8,
+    pub formality: u8,
+    pub humor: u8,
+    pub aggression: u8,
+    /// Nombre de messages précédents rappelés au modèle.
+    pub memory_depth: u8,
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        Self { warmth: 50, verbosity: 50, formality: 50, humor: 50, aggression: 50, memory_depth: 5 }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CharacterPreset {
+    pub id: String,
+    pub name: String,
+    pub personality: Personality,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Persona {
     pub id: String,
     pub display_name: String,
@@ -43,15 +93,47 @@ pub struct Persona {
     pub img: String,
 }
 
+#[derive(Clone)]
 pub struct AppState {
-    pub services_running: Arc<AtomicBool>,
+    pub settings: Arc<SettingsStore>,
+    pub supervisor: Arc<Supervisor>,
+    pub health: Arc<HealthMonitor>,
+    pub http: Arc<HttpClient>,
+    pub vault: Arc<Vault>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppState {
+    /// État partagé par l'application et la CLI ; rien n'est démarré ici.
+    pub fn new() -> Self {
+        let settings = Arc::new(SettingsStore::load());
+        let http = Arc::new(HttpClient::new());
+        let supervisor = Arc::new(Supervisor::new(Arc::clone(&settings), Arc::clone(&http)));
+        let health = Arc::new(HealthMonitor::new(Arc::clone(&supervisor), Arc::clone(&settings), Arc::clone(&http)));
+
+        Self {
+            settings,
+            supervisor,
+            health,
+            http,
+            vault: Arc::new(Vault::new()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatMessage {
     pub role: String,        
     pub content: String,
     pub timestamp: Option<String>, 
+    /// Personnage qui parle, dans une conversation de groupe.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -61,6 +143,260 @@ pub struct AskRequestForChat {
     pub user_id: String,
     pub model: String,
     pub memory: Vec<ChatMessage>,
+    /// Langue dans laquelle le personnage doit répondre.
+    pub language: Locale,
+    /// Réponse en NDJSON, fragment par fragment (`StreamChunk`).
+    pub stream: bool,
+    /// Conversation de groupe : l'historique vient de `memory` et n'est pas enregistré
+    /// par les services.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// Les autres personnages présents dans la scène.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene: Option<SceneState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relationship: Option<RelationshipState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene_preset: Option<ScenePreset>,
+    /// Transmise par l'application : avec le coffre activé, les services ne peuvent pas
+    /// lire le fichier des personas.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persona: Option<Persona>,
+    /// Personnage qui répond, `{{char}}` et `{{user}}` développés : l'API Go le préfère
+    /// à sa copie de `characters.json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character: Option<Character>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Mood {
+    #[default]
+    Neutral,
+    Joyful,
+    Affectionate,
+    Sad,
+    Angry,
+    Afraid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Energy {
+    Low,
+    #[default]
+    Moderate,
+    High,
+}
+
+/// État émotionnel d'un personnage envers une persona, mis à jour après chaque réplique.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RelationshipState {
+    pub mood: Mood,
+    pub energy: Energy,
+    /// De -100 (hostile) à 100 (très attaché).
+    pub affinity: i32,
+    /// De 0 à 100.
+    pub trust: i32,
+    /// De 0 à 100.
+    pub tension: i32,
+    pub turns: u32,
+    pub updated_at: Option<String>,
+}
+
+impl Default for RelationshipState {
+    fn default() -> Self {
+        Self {
+            mood: Mood::default(),
+            energy: Energy::default(),
+            affinity: 0,
+            trust: 50,
+            tension: 0,
+            turns: 0,
+            updated_at: None,
+        }
+    }
+}
+
+/// Conversation à laquelle se rattachent un historique et un état de scène.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChatSession {
+    Direct { character_id: String, persona_id: String },
+    Group { group_id: String },
+}
+
+/// Ce que le narrateur sait de la scène ; injecté dans les prompts de la conversation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct SceneState {
+    pub location: String,
+    pub time_of_day: String,
+    /// Personnages présents, y compris les figurants animés par le narrateur.
+    pub present_characters: Vec<String>,
+    pub objectives: Vec<String>,
+    /// Préréglage de scène (ton, cadre, échantillonnage) appliqué à la conversation.
+    pub preset: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// Ambiance de scène ajoutée au prompt, avec des réglages d'échantillonnage optionnels.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScenePreset {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub setting: String,
+    pub tone: String,
+    pub focus: String,
+    #[serde(default)]
+    pub sampling: SamplingOverrides,
+    /// Fourni avec l'application : ni modifiable ni supprimable.
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+/// Options Ollama remplacées pendant la scène ; les autres gardent leur valeur par défaut.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct SamplingOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SceneChanged {
+    pub session: ChatSession,
+    pub scene: SceneState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NarrateRequest {
+    pub character_ids: Vec<String>,
+    pub persona_id: String,
+    pub direction: String,
+    pub scene: SceneState,
+    pub scene_preset: Option<ScenePreset>,
+    pub persona: Option<Persona>,
+    pub memory: Vec<ChatMessage>,
+    pub model: String,
+    pub language: Locale,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Narration {
+    pub message: ChatMessage,
+    pub scene: SceneState,
+    pub scene_changed: bool,
+}
+
+/// Façon de choisir le personnage qui répond dans une conversation de groupe.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnStrategy {
+    /// Chacun son tour, dans l'ordre des membres.
+    #[default]
+    RoundRobin,
+    /// Le personnage nommé dans le message, sinon chacun son tour.
+    Addressed,
+    /// Le modèle choisit qui parle ensuite.
+    LlmChosen,
+    /// L'utilisateur choisit à chaque message.
+    Manual,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupChat {
+    pub id: String,
+    pub name: String,
+    pub character_ids: Vec<String>,
+    pub persona_id: String,
+    #[serde(default)]
+    pub strategy: TurnStrategy,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NextSpeakerRequest {
+    pub character_ids: Vec<String>,
+    pub question: String,
+    pub memory: Vec<ChatMessage>,
+    pub model: String,
+    pub language: Locale,
+}
+
+/// Demande de brouillon de personnage ; `previous_error` rappelle au modèle pourquoi sa
+/// réponse précédente a été refusée.
+#[derive(Debug, Serialize)]
+pub struct CharacterDraftRequest {
+    pub concept: String,
+    pub tags: Vec<String>,
+    pub model: String,
+    pub language: Locale,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_error: Option<String>,
+}
+
+/// Ce que le modèle doit renvoyer ; converti en `Character` une fois validé.
+#[derive(Debug, Deserialize)]
+pub struct CharacterDraft {
+    pub name: String,
+    pub tagline: String,
+    pub description: String,
+    pub greeting: String,
+    pub definition: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub alternate_greetings: Vec<String>,
+    #[serde(default)]
+    pub example_dialogues: Vec<ExampleDialogue>,
+    #[serde(default)]
+    pub personality: Option<Personality>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NextSpeakerResponse {
+    pub speaker_id: Option<String>,
+}
+
+/// Ligne du flux renvoyé par `/ask` en mode `stream` : des fragments, puis une ligne
+/// `done` portant la réponse complète nettoyée, ou une ligne `error`.
+#[derive(Debug, Deserialize)]
+pub struct StreamChunk {
+    #[serde(default)]
+    pub response: String,
+    #[serde(default)]
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+/// Corps de `/v1/chat/completions` ; `model` est l'identifiant du personnage et `user`
+/// celui de la persona.
+#[derive(Debug, Deserialize)]
+pub struct OpenAiChatRequest {
+    pub model: String,
+    pub messages: Vec<OpenAiMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAiMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -71,3 +407,411 @@ pub struct RecentChat {
     pub img: Option<String>,
     pub last_used: u64,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportOptions {
+    pub mode: ImportMode,
+    pub on_conflict: ConflictStrategy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenamedEntry {
+    pub kind: String,
+    pub old_id: String,
+    pub new_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportReport {
+    pub characters_imported: usize,
+    pub personas_imported: usize,
+    pub groups_imported: usize,
+    pub histories_imported: usize,
+    pub assets_imported: usize,
+    pub skipped: Vec<String>,
+    pub renamed: Vec<RenamedEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+    Text,
+    Jsonl,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    pub hourly: usize,
+    pub daily: usize,
+    pub weekly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            hourly: 24,
+            daily: 7,
+            weekly: 4,
+        }
+    }
+}
+
+/// Serveur local compatible OpenAI ; pris en compte au prochain démarrage.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OpenAiServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Jeton attendu dans `Authorization: Bearer …`.
+    pub token: String,
+    /// Persona utilisée quand la requête ne précise pas `user`.
+    pub default_persona_id: Option<String>,
+}
+
+impl Default for OpenAiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8787,
+            token: String::new(),
+            default_persona_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at: String,
+    pub file_count: usize,
+    pub histories: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SnapshotDiff {
+    pub files_added: Vec<String>,
+    pub files_removed: Vec<String>,
+    pub files_modified: Vec<String>,
+    pub characters_added: Vec<String>,
+    pub characters_removed: Vec<String>,
+    pub characters_modified: Vec<String>,
+    pub personas_added: Vec<String>,
+    pub personas_removed: Vec<String>,
+    pub personas_modified: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoredAsset {
+    pub id: String,
+    pub hash: String,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnails: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceKind {
+    Ollama,
+    Python,
+    Go,
+}
+
+impl ServiceKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceKind::Ollama => "Ollama",
+            ServiceKind::Python => "Python LLM",
+            ServiceKind::Go => "Go API",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceState {
+    Stopped,
+    Starting,
+    Running,
+    Backoff,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceStatus {
+    pub service: ServiceKind,
+    pub state: ServiceState,
+    pub port: u16,
+    /// Instance lancée hors de l'application : on l'utilise sans pouvoir l'arrêter.
+    pub adopted: bool,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub uptime_secs: Option<u64>,
+    pub last_exit: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceEvent {
+    pub service: ServiceKind,
+    pub state: ServiceState,
+    pub port: u16,
+    pub elapsed_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Échec de démarrage ou de fonctionnement d'un service annexe, transmis tel quel à l'interface.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ServiceStartError {
+    ExecutableNotFound { service: ServiceKind, program: String },
+    SpawnFailed { service: ServiceKind, message: String },
+    PortUnavailable { service: ServiceKind, port: u16 },
+    NotReady { service: ServiceKind, timeout_secs: u64 },
+    ExitedDuringStartup { service: ServiceKind },
+    Crashed { service: ServiceKind, status: String },
+}
+
+impl ServiceStartError {
+    /// Conseil à afficher à l'utilisateur pour résoudre le problème.
+    pub fn hint(&self) -> String {
+        match self {
+            ServiceStartError::ExecutableNotFound { service: ServiceKind::Ollama, .. } => {
+                t(Msg::HintInstallOllama).into()
+            }
+            ServiceStartError::ExecutableNotFound { service: ServiceKind::Python, .. } => {
+                t(Msg::HintInstallPython).into()
+            }
+            ServiceStartError::ExecutableNotFound { service: ServiceKind::Go, .. } => t(Msg::HintInstallGo).into(),
+            ServiceStartError::SpawnFailed { .. } => t(Msg::HintCheckExecutable).into(),
+            ServiceStartError::PortUnavailable { port, .. } => tf(Msg::HintFreePort, &[port]),
+            ServiceStartError::NotReady { service: ServiceKind::Ollama, .. } => t(Msg::HintPullModel).into(),
+            ServiceStartError::NotReady { .. } => t(Msg::HintSlowService).into(),
+            ServiceStartError::ExitedDuringStartup { service: ServiceKind::Python } => {
+                t(Msg::HintPythonDependencies).into()
+            }
+            ServiceStartError::ExitedDuringStartup { service: ServiceKind::Go } => t(Msg::HintGoBuild).into(),
+            ServiceStartError::ExitedDuringStartup { .. } | ServiceStartError::Crashed { .. } => {
+                t(Msg::HintCheckOutput).into()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ServiceStartError::ExecutableNotFound { service, program } => {
+                tf(Msg::ExecutableNotFound, &[&service.label(), program])
+            }
+            ServiceStartError::SpawnFailed { service, message } => tf(Msg::SpawnFailed, &[&service.label(), message]),
+            ServiceStartError::PortUnavailable { service, port } => {
+                tf(Msg::PortUnavailable, &[&service.label(), port])
+            }
+            ServiceStartError::NotReady { service, timeout_secs } => {
+                tf(Msg::NotReady, &[&service.label(), timeout_secs])
+            }
+            ServiceStartError::ExitedDuringStartup { service } => tf(Msg::ExitedDuringStartup, &[&service.label()]),
+            ServiceStartError::Crashed { service, status } => tf(Msg::Crashed, &[&service.label(), status]),
+        };
+        f.write_str(&message)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AppMode {
+    Starting,
+    Full,
+    /// Au moins un service manque : le chat est coupé, la consultation et l'édition restent possibles.
+    Degraded,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ServiceIssue {
+    pub service: ServiceKind,
+    pub state: ServiceState,
+    pub message: String,
+    pub hint: String,
+    pub error: Option<ServiceStartError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SystemStatus {
+    pub mode: AppMode,
+    pub chat_available: bool,
+    pub issues: Vec<ServiceIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    /// Messages du superviseur lui-même (arrêts, redémarrages, échecs de démarrage).
+    Supervisor,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogLine {
+    /// Numéro croissant, commun à tous les services : sert de curseur pour `since`.
+    pub seq: u64,
+    pub service: ServiceKind,
+    pub timestamp: String,
+    pub stream: LogStream,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Healthy,
+    /// Le service répond, mais avec un statut d'erreur.
+    Unhealthy,
+    Unreachable,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceHealth {
+    pub service: ServiceKind,
+    pub state: HealthState,
+    pub latency_ms: Option<u64>,
+    pub version: Option<String>,
+    pub model: Option<String>,
+    pub last_error: Option<String>,
+    pub uptime_secs: Option<u64>,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub services: Vec<ServiceHealth>,
+}
+
+/// Réglages de l'application, enregistrés dans `data/settings.json`. Les champs absents
+/// du fichier prennent leur valeur par défaut.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub locale: Locale,
+    pub model: String,
+    pub ollama_port: u16,
+    pub python_port: u16,
+    pub go_port: u16,
+    pub auto_start_services: bool,
+    pub max_restarts: u32,
+    pub health_interval_secs: u64,
+    pub snapshot_interval_minutes: u64,
+    pub snapshot_retention: RetentionPolicy,
+    pub openai_server: OpenAiServerSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            model: "dolphin-mistral".to_string(),
+            ollama_port: 11434,
+            python_port: 5050,
+            go_port: 8080,
+            auto_start_services: true,
+            max_restarts: 5,
+            health_interval_secs: 10,
+            snapshot_interval_minutes: 15,
+            snapshot_retention: RetentionPolicy::default(),
+            openai_server: OpenAiServerSettings::default(),
+        }
+    }
+}
+
+/// Langue des messages de l'application et des réponses des personnages.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Fr,
+    En,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fr" => Ok(Locale::Fr),
+            "en" => Ok(Locale::En),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Settings {
+    /// Port préféré du service ; il n'est pris en compte qu'à son prochain démarrage.
+    pub fn port(&self, kind: ServiceKind) -> u16 {
+        match kind {
+            ServiceKind::Ollama => self.ollama_port,
+            ServiceKind::Python => self.python_port,
+            ServiceKind::Go => self.go_port,
+        }
+    }
+}
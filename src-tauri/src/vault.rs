@@ -0,0 +1,386 @@
+use crate::error::{AppError, AppResult};
+use crate::i18n::{t, tf, Msg};
+use crate::file_utils::VAULT_FILE_PATH;
+use crate::types::VaultStatus;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use zeroize::Zeroizing;
+
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+
+const MAGIC: &[u8] = b"PAIVAULT1";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+// Paramètres Argon2id recommandés par l'OWASP (19 MiB, 2 passes).
+const ARGON2_M_COST: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+type Key = Zeroizing<[u8; KEY_LEN]>;
+
+/// Contenu de `data/vault.json`. La clé de chiffrement des données est aléatoire et
+/// stockée enveloppée par une clé dérivée de la phrase secrète : changer de phrase
+/// secrète ne demande donc pas de rechiffrer les fichiers.
+#[derive(Serialize, Deserialize)]
+struct VaultHeader {
+    version: u32,
+    kdf: String,
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    wrapped_key: String,
+}
+
+/// Clé de données déverrouillée, conservée en mémoire dans `AppState` tant que
+/// l'application tourne. Les fichiers chiffrés portent l'extension `.enc`, ce qui les rend
+/// invisibles aux services Go et Python qui ne lisent que le JSON en clair.
+pub struct Vault {
+    key: RwLock<Option<Key>>,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vault {
+    pub fn new() -> Self {
+        Self {
+            key: RwLock::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        Path::new(VAULT_FILE_PATH).exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.read().map(|k| k.is_some()).unwrap_or(false)
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        VaultStatus {
+            enabled: self.is_enabled(),
+            unlocked: self.is_unlocked(),
+        }
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> AppResult<()> {
+        let header = read_header()?;
+        let key = unwrap_key(&header, passphrase)?;
+
+        *self.key.write().map_err(|_| AppError::conflict(t(Msg::VaultUnavailable)))? = Some(key);
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        if let Ok(mut key) = self.key.write() {
+            *key = None;
+        }
+    }
+
+    /// Active le coffre et chiffre les fichiers en clair passés en paramètre.
+    pub fn enable(&self, passphrase: &str, plaintext_files: &[PathBuf]) -> AppResult<()> {
+        if self.is_enabled() {
+            return Err(AppError::conflict(t(Msg::VaultAlreadyEnabled)));
+        }
+        validate_passphrase(passphrase)?;
+
+        let mut key: Key = Zeroizing::new([0u8; KEY_LEN]);
+        OsRng.fill_bytes(key.as_mut());
+
+        write_header(&wrap_key(&key, passphrase)?)?;
+        *self.key.write().map_err(|_| AppError::conflict(t(Msg::VaultUnavailable)))? = Some(key);
+
+        for path in plaintext_files {
+            if let Ok(data) = fs::read(path) {
+                self.write(path, &data)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn change_passphrase(&self, old_passphrase: &str, new_passphrase: &str) -> AppResult<()> {
+        validate_passphrase(new_passphrase)?;
+
+        let key = unwrap_key(&read_header()?, old_passphrase)?;
+        write_header(&wrap_key(&key, new_passphrase)?)?;
+
+        *self.key.write().map_err(|_| AppError::conflict(t(Msg::VaultUnavailable)))? = Some(key);
+        Ok(())
+    }
+
+    /// Lit `path`, en déchiffrant sa version `.enc` si elle existe.
+    /// Renvoie `None` si aucune des deux versions n'existe.
+    pub fn read(&self, path: &Path) -> AppResult<Option<Vec<u8>>> {
+        let sealed = encrypted_path(path);
+        if sealed.exists() {
+            return self.open(&sealed).map(Some);
+        }
+
+        match fs::read(path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::read(path, e)),
+        }
+    }
+
+    /// Écrit `path` chiffré si le coffre est activé (et supprime alors la version en clair),
+    /// en clair sinon.
+    pub fn write(&self, path: &Path, data: &[u8]) -> AppResult<()> {
+        if !self.is_enabled() {
+            return fs::write(path, data).map_err(|e| AppError::write(path, e));
+        }
+
+        let sealed = self.seal(path, data)?;
+        let sealed_path = encrypted_path(path);
+        fs::write(&sealed_path, sealed).map_err(|e| AppError::write(&sealed_path, e))?;
+
+        if path.exists() {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &Path) -> AppResult<()> {
+        for candidate in [path.to_path_buf(), encrypted_path(path)] {
+            if candidate.exists() {
+                fs::remove_file(&candidate).map_err(|e| AppError::delete(&candidate, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Déchiffre un fichier `.enc` existant.
+    pub fn open(&self, sealed_path: &Path) -> AppResult<Vec<u8>> {
+        let data = fs::read(sealed_path).map_err(|e| AppError::read(sealed_path, e))?;
+        self.open_bytes(&plain_name(sealed_path), &data)
+    }
+
+    /// Déchiffre le contenu d'un fichier `.enc` ; `name` est le nom du fichier en clair.
+    pub fn open_bytes(&self, name: &str, data: &[u8]) -> AppResult<Vec<u8>> {
+        let guard = self.key.read().map_err(|_| AppError::conflict(t(Msg::VaultUnavailable)))?;
+        let key = guard.as_ref().ok_or_else(|| AppError::conflict(t(Msg::VaultLocked)))?;
+
+        let body = data
+            .strip_prefix(MAGIC)
+            .filter(|body| body.len() > NONCE_LEN)
+            .ok_or_else(|| AppError::parse(name, t(Msg::InvalidSealedFile)))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+        XChaCha20Poly1305::new(key.as_ref().into())
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::parse(name, t(Msg::DecryptionFailed)))
+    }
+
+    fn seal(&self, path: &Path, data: &[u8]) -> AppResult<Vec<u8>> {
+        self.seal_bytes(path.file_name().and_then(|n| n.to_str()).unwrap_or_default(), data)
+    }
+
+    /// Chiffre `data` pour un fichier nommé `name` (nom du fichier en clair).
+    pub fn seal_bytes(&self, name: &str, data: &[u8]) -> AppResult<Vec<u8>> {
+        let guard = self.key.read().map_err(|_| AppError::conflict(t(Msg::VaultUnavailable)))?;
+        let key = guard.as_ref().ok_or_else(|| AppError::conflict(t(Msg::VaultLocked)))?;
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: data,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::parse(name, t(Msg::EncryptionFailed)))?;
+
+        Ok([MAGIC, nonce.as_slice(), &ciphertext].concat())
+    }
+}
+
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ENCRYPTED_EXTENSION);
+    PathBuf::from(name)
+}
+
+fn plain_name(sealed_path: &Path) -> String {
+    sealed_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.trim_end_matches(&format!(".{}", ENCRYPTED_EXTENSION)).to_string())
+        .unwrap_or_default()
+}
+
+fn validate_passphrase(passphrase: &str) -> AppResult<()> {
+    if passphrase.chars().count() < 8 {
+        return Err(AppError::invalid_field(
+            "passphrase",
+            t(Msg::PassphraseTooShort),
+        ));
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], m_cost: u32, t_cost: u32, p_cost: u32) -> AppResult<Key> {
+    let params = Params::new(m_cost, t_cost, p_cost, Some(KEY_LEN))
+        .map_err(|e| AppError::parse(VAULT_FILE_PATH, tf(Msg::InvalidKdfParams, &[&e])))?;
+
+    let mut key: Key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| AppError::validation(tf(Msg::KeyDerivationFailed, &[&e])))?;
+
+    Ok(key)
+}
+
+fn wrap_key(key: &Key, passphrase: &str) -> AppResult<VaultHeader> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let kek = derive_key(passphrase, &salt, ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let wrapped = XChaCha20Poly1305::new(kek.as_ref().into())
+        .encrypt(&nonce, key.as_slice())
+        .map_err(|_| AppError::parse(VAULT_FILE_PATH, t(Msg::EncryptionFailed)))?;
+
+    Ok(VaultHeader {
+        version: 1,
+        kdf: "argon2id".to_string(),
+        salt: BASE64.encode(salt),
+        m_cost: ARGON2_M_COST,
+        t_cost: ARGON2_T_COST,
+        p_cost: ARGON2_P_COST,
+        wrapped_key: BASE64.encode([nonce.as_slice(), &wrapped].concat()),
+    })
+}
+
+fn unwrap_key(header: &VaultHeader, passphrase: &str) -> AppResult<Key> {
+    let invalid_header = || AppError::parse(VAULT_FILE_PATH, t(Msg::InvalidVaultHeader));
+
+    let salt = BASE64.decode(&header.salt).map_err(|_| invalid_header())?;
+    let wrapped = BASE64.decode(&header.wrapped_key).map_err(|_| invalid_header())?;
+    if wrapped.len() <= NONCE_LEN {
+        return Err(invalid_header());
+    }
+
+    let kek = derive_key(passphrase, &salt, header.m_cost, header.t_cost, header.p_cost)?;
+    let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+    let plain = Zeroizing::new(
+        XChaCha20Poly1305::new(kek.as_ref().into())
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::invalid_field("passphrase", t(Msg::PassphraseIncorrect)))?,
+    );
+
+    let mut key: Key = Zeroizing::new([0u8; KEY_LEN]);
+    if plain.len() != KEY_LEN {
+        return Err(invalid_header());
+    }
+    key.copy_from_slice(&plain);
+    Ok(key)
+}
+
+fn read_header() -> AppResult<VaultHeader> {
+    let content = fs::read_to_string(VAULT_FILE_PATH).map_err(|_| AppError::conflict(t(Msg::VaultNotEnabled)))?;
+    serde_json::from_str(&content).map_err(|e| AppError::parse(VAULT_FILE_PATH, e))
+}
+
+fn write_header(header: &VaultHeader) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(header).map_err(|e| AppError::parse(VAULT_FILE_PATH, e))?;
+    fs::write(VAULT_FILE_PATH, content).map_err(|e| AppError::write(VAULT_FILE_PATH, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked_vault() -> Vault {
+        let mut key: Key = Zeroizing::new([0u8; KEY_LEN]);
+        OsRng.fill_bytes(key.as_mut());
+        Vault {
+            key: RwLock::new(Some(key)),
+        }
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let vault = unlocked_vault();
+        let sealed = vault.seal_bytes("personas.json", b"[{\"id\":\"alice_1\"}]").unwrap();
+
+        assert!(sealed.starts_with(MAGIC));
+        assert_eq!(vault.open_bytes("personas.json", &sealed).unwrap(), b"[{\"id\":\"alice_1\"}]");
+    }
+
+    #[test]
+    fn open_rejects_other_file_name_or_key() {
+        let vault = unlocked_vault();
+        let sealed = vault.seal_bytes("a_1_b_2.json", b"[]").unwrap();
+
+        assert!(vault.open_bytes("c_3_d_4.json", &sealed).is_err());
+        assert!(unlocked_vault().open_bytes("a_1_b_2.json", &sealed).is_err());
+    }
+
+    #[test]
+    fn open_rejects_tampered_data() {
+        let vault = unlocked_vault();
+        let mut sealed = vault.seal_bytes("personas.json", b"[]").unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+
+        assert!(vault.open_bytes("personas.json", &sealed).is_err());
+        assert!(vault.open_bytes("personas.json", b"not sealed").is_err());
+    }
+
+    #[test]
+    fn locked_vault_cannot_seal_or_open() {
+        let sealed = unlocked_vault().seal_bytes("personas.json", b"[]").unwrap();
+        let locked = Vault::new();
+
+        assert!(locked.seal_bytes("personas.json", b"[]").is_err());
+        assert!(locked.open_bytes("personas.json", &sealed).is_err());
+    }
+
+    #[test]
+    fn wrapped_key_unwraps_with_the_same_passphrase_only() {
+        let mut key: Key = Zeroizing::new([0u8; KEY_LEN]);
+        OsRng.fill_bytes(key.as_mut());
+        let header = wrap_key(&key, "correct horse battery").unwrap();
+
+        assert_eq!(unwrap_key(&header, "correct horse battery").unwrap().as_slice(), key.as_slice());
+        assert!(unwrap_key(&header, "wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn corrupted_header_is_rejected() {
+        let key: Key = Zeroizing::new([7u8; KEY_LEN]);
+        let mut header = wrap_key(&key, "correct horse battery").unwrap();
+        header.wrapped_key = BASE64.encode([0u8; NONCE_LEN]);
+
+        assert!(unwrap_key(&header, "correct horse battery").is_err());
+    }
+
+    #[test]
+    fn short_passphrases_are_refused() {
+        assert!(validate_passphrase("short").is_err());
+        assert!(validate_passphrase("long enough").is_ok());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::error::{AppError, AppResult};
+use crate::http::Retry;
+use crate::file_utils;
+use crate::i18n::{t, tf, Msg};
+use crate::personality;
+use crate::types::{AppState, AskResponse, Character, CharacterDraft, CharacterDraftRequest, ServiceKind};
+use std::collections::HashMap;
+
+/// Nombre de réponses du modèle examinées avant d'abandonner.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Fait rédiger par le modèle local un personnage à partir de `concept`. Une réponse
+/// illisible ou incomplète est redemandée, avec la raison du refus. Le brouillon n'est pas
+/// enregistré : il passe par `update_character` une fois relu.
+pub async fn generate_character_draft(state: &AppState, concept: &str, tags: &[String]) -> AppResult<Character> {
+    let concept = concept.trim();
+    if concept.is_empty() {
+        return Err(AppError::invalid_field("concept", t(Msg::DraftConceptRequired)));
+    }
+
+    let settings = state.settings.get();
+    let mut request = CharacterDraftRequest {
+        concept: concept.to_string(),
+        tags: tags.iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect(),
+        model: settings.model,
+        language: settings.locale,
+        previous_error: None,
+    };
+
+    let url = format!("{}/character-draft", state.supervisor.base_url(ServiceKind::Go));
+    for attempt in 1..=MAX_ATTEMPTS {
+        let answer = state
+            .http
+            .post_json::<CharacterDraftRequest, AskResponse>(ServiceKind::Go, &url, &request, Retry::ConnectOnly)
+            .await?
+            .answer;
+
+        match parse_draft(&answer) {
+            Ok(character) => return Ok(character),
+            Err(reason) => {
+                eprintln!("⚠️ Brouillon refusé (essai {}/{}): {}", attempt, MAX_ATTEMPTS, reason);
+                request.previous_error = Some(reason);
+            }
+        }
+    }
+
+    let reason = request.previous_error.unwrap_or_default();
+    Err(AppError::backend(ServiceKind::Python.label(), None, tf(Msg::DraftMalformed, &[&MAX_ATTEMPTS, &reason])))
+}
+
+/// Lit l'objet JSON de la réponse (le modèle l'entoure parfois de texte ou de ```) et le
+/// convertit en `Character`.
+fn parse_draft(answer: &str) -> Result<Character, String> {
+    let json = match (answer.find('{'), answer.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &answer[start..=end],
+        _ => answer,
+    };
+    let draft: CharacterDraft = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+    let required = [
+        ("name", &draft.name),
+        ("tagline", &draft.tagline),
+        ("description", &draft.description),
+        ("greeting", &draft.greeting),
+        ("definition", &draft.definition),
+    ];
+    if let Some((field, _)) = required.iter().find(|(_, value)| value.trim().is_empty()) {
+        return Err(tf(Msg::DraftFieldMissing, &[field]));
+    }
+
+    let name = draft.name.trim().to_string();
+    let tags: Vec<String> = draft.tags.iter().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect();
+
+    let character = Character {
+        id: file_utils::id_from_name(&name, "character"),
+        name,
+        tagline: draft.tagline.trim().to_string(),
+        description: draft.description.trim().to_string(),
+        greeting: draft.greeting.trim().to_string(),
+        definition: draft.definition.trim().to_string(),
+        tags: HashMap::from([("general".to_string(), tags)]),
+        img: "placeholder.png".to_string(),
+        alternate_greetings: trimmed(draft.alternate_greetings),
+        example_dialogues: draft
+            .example_dialogues
+            .into_iter()
+            .filter(|d| !d.user.trim().is_empty() && !d.character.trim().is_empty())
+            .collect(),
+        personality: draft.personality,
+        default_persona_id: None,
+    };
+    personality::validate(&character).map_err(|e| e.to_string())?;
+    Ok(character)
+}
+
+fn trimmed(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAFT: &str = r#"{"name": " Mira  Vale ", "tagline": "Cartographe", "description": "Elle dessine les côtes.",
+        "greeting": "Bonjour.", "definition": "Curieuse.", "tags": [" Aventure ", ""],
+        "alternate_greetings": ["  Salut. ", " "],
+        "example_dialogues": [{"user": "Où va-t-on ?", "character": "Au nord."}, {"user": "", "character": "…"}]}"#;
+
+    #[test]
+    fn draft_is_extracted_from_surrounding_text_and_cleaned() {
+        let character = parse_draft(&format!("Voici le personnage :\n```json\n{}\n```", DRAFT)).unwrap();
+
+        assert_eq!(character.name, "Mira  Vale");
+        assert!(character.id.starts_with("mira-vale_"));
+        assert_eq!(character.tags["general"], vec!["aventure"]);
+        assert_eq!(character.alternate_greetings, vec!["Salut."]);
+        assert_eq!(character.example_dialogues.len(), 1);
+    }
+
+    #[test]
+    fn empty_required_field_is_reported() {
+        let error = parse_draft(&DRAFT.replace("\"Bonjour.\"", "\"  \"")).unwrap_err();
+        assert!(error.contains("greeting"));
+    }
+
+    #[test]
+    fn malformed_json_and_invalid_personality_are_rejected() {
+        assert!(parse_draft("Je ne peux pas.").is_err());
+        assert!(parse_draft(&DRAFT.replace("\"tags\"", "\"personality\": {\"memory_depth\": 0}, \"tags\"")).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 import { BrowserRouter as Router, Routes, Route } from "react-router-dom";
 import Sidebar from "./components/Sidebar";
 import Header from "./components/Header";
+import ServiceStatusBanner from "./components/ServiceStatusBanner";
 import Dashboard from "./pages/Dashboard";
 import ChatHistoryPage from "./pages/ChatHistoryPage";
 import CreateCharacter from "./pages/CreateCharacter";
@@ -23,6 +24,7 @@ function App() {
         <Sidebar />
         <div className="flex flex-col flex-1 overflow-y-auto min-h-screen">
           <Header />
+          <ServiceStatusBanner />
           <Routes>
             <Route path="/" element={<Dashboard />} />
             <Route path="/create-character" element={<CreateCharacter />} />
@@ -0,0 +1,51 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+interface ServiceIssue {
+  service: string;
+  state: string;
+  message: string;
+  hint: string;
+}
+
+interface SystemStatus {
+  mode: "starting" | "full" | "degraded";
+  chat_available: boolean;
+  issues: ServiceIssue[];
+}
+
+const ServiceStatusBanner = () => {
+  const [status, setStatus] = useState<SystemStatus | null>(null);
+
+  useEffect(() => {
+    invoke<SystemStatus>("system_status")
+      .then(setStatus)
+      .catch((err) => console.error("Failed to load system status", err));
+
+    const unlisten = listen<SystemStatus>("system-status", (event) => setStatus(event.payload));
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  if (!status || status.mode !== "degraded") return null;
+
+  return (
+    <div className="px-6 py-3 bg-catppuccin-surface1 border-b border-zinc-800 text-sm">
+      <strong className="text-catppuccin-text">Offline mode:</strong>{" "}
+      <span className="text-catppuccin-subtext1">
+        chat is unavailable, you can still browse and edit your characters and personas.
+      </span>
+      <ul className="mt-1 text-catppuccin-subtext1">
+        {status.issues.map((issue) => (
+          <li key={issue.service}>
+            {issue.message} — {issue.hint}
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+};
+
+export default ServiceStatusBanner;
@@ -17,3 +17,26 @@ export function formatDate(input: string | number): string {
 export function absoluteUrl(path: string) {
   return `${process.env.NEXT_PUBLIC_APP_URL}${path}`
 }
+
+export interface AppError {
+  code:
+    | "NOT_FOUND"
+    | "VALIDATION"
+    | "IO"
+    | "PARSE"
+    | "BACKEND"
+    | "TIMEOUT"
+    | "CANCELLED"
+    | "CONFLICT"
+  message: string
+  context: Record<string, unknown>
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err
+}
+
+export function describeError(err: unknown): string {
+  if (isAppError(err)) return err.message
+  return String(err)
+}
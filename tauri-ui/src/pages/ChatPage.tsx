@@ -2,6 +2,7 @@ import { useEffect, useRef, useState } from "react";
 import { useParams, useNavigate } from "react-router-dom";
 import ReactMarkdown from "react-markdown";
 import { invoke } from "@tauri-apps/api/core";
+import { describeError, isAppError } from "../lib/utils";
 
 interface Message {
   role: "user" | "assistant";
@@ -55,7 +56,7 @@ const ChatPage = () => {
           console.log("CharacterID:", characterId);
           console.log("PersonaID:", personaId);
 
-            const previousMessages = await invoke<Message[]>("load_chat_history", {
+            const previousMessages = await invoke<Message[]>("start_chat_session", {
             characterId,
             personaId
           });
@@ -143,7 +144,7 @@ const ChatPage = () => {
         timestamp: new Date()
       };
       setMessages((prev) => [...prev, errorMessage]);
-      setError("Erreur de communication avec le LLM");
+      setError(isAppError(err) ? describeError(err) : "Erreur de communication avec le LLM");
     } finally {
       setLoading(false);
     }
@@ -156,7 +157,17 @@ const ChatPage = () => {
         characterId: characterId
       });
 
-      if (character) {
+      const seeded = await invoke<Message[]>("start_chat_session", {
+        characterId,
+        personaId
+      });
+
+      if (seeded.length > 0) {
+        setMessages(seeded.map(msg => ({
+          ...msg,
+          timestamp: msg.timestamp ? new Date(msg.timestamp) : undefined
+        })));
+      } else if (character) {
         setMessages([{
         role: "assistant",
         content: character.greeting || `Salut ! Je suis ${character.name}.`,
@@ -1,5 +1,5 @@
 import { useEffect, useState } from "react";
-import { useNavigate } from "react-router-dom";
+import { useNavigate, useSearchParams } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
 
 interface Character {
@@ -7,6 +7,7 @@ interface Character {
     name: string;
     tagline: string;
     img: string;
+    default_persona_id?: string;
 }
 
 interface Persona {
@@ -17,6 +18,7 @@ interface Persona {
 
 const ChatSetup = () => {
     const navigate = useNavigate();
+    const [searchParams] = useSearchParams();
     const [characters, setCharacters] = useState<Character[]>([])
     const [personas, setPersonas] = useState<Persona[]>([])
     const [selectedCharacter, setSelectedCharacter] = useState<Character | null>(null);
@@ -27,6 +29,25 @@ const ChatSetup = () => {
         invoke("load_personas").then((res:any) => setPersonas(res)).catch(console.error);
     }, []);
 
+    // Depuis une fiche personnage : on ouvre directement la conversation avec sa persona par défaut.
+    useEffect(() => {
+        const characterId = searchParams.get("characterId");
+        const char = characters.find((c) => c.id === characterId);
+        if (!char || personas.length === 0) return;
+
+        if (char.default_persona_id && personas.some((p) => p.id === char.default_persona_id)) {
+            navigate(`/chat/${char.id}/${char.default_persona_id}`, { replace: true });
+        } else {
+            setSelectedCharacter(char);
+        }
+    }, [characters, personas, searchParams]);
+
+    const selectCharacter = (char: Character) => {
+        setSelectedCharacter(char);
+        const fallback = personas.find((p) => p.id === char.default_persona_id);
+        if (fallback) setSelectedPersona(fallback);
+    };
+
     const handleStartChat = () => {
         if (selectedCharacter && selectedPersona) {
             navigate(`/chat/${selectedCharacter.id}/${selectedPersona.id}`);
@@ -55,7 +76,7 @@ const ChatSetup = () => {
             {characters.map((char) => (
               <div
                 key={char.id}
-                onClick={() => setSelectedCharacter(char)}
+                onClick={() => selectCharacter(char)}
                 className={`cursor-pointer border rounded-lg p-4 flex gap-4 items-center transition ${
                   selectedCharacter?.id === char.id
                     ? "border-purple-600 bg-purple-700/20"
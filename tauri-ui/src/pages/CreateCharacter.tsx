@@ -64,14 +64,11 @@ const CreateCharacter = () => {
         const reader = new FileReader();
         reader.onload = () => {
           const buffer = reader.result as ArrayBuffer;
-          const blob = new Blob([buffer]);
-          const newFilePath = `/assets/characters/${file.name}`;
 
-          invoke("copy_image_to_path", {
-            fileName: file.name,
+          invoke<{ id: string }>("store_image_asset", {
             data: Array.from(new Uint8Array(buffer))
-          }).then(() => {
-            setForm({...form, img: newFilePath });
+          }).then((asset) => {
+            setForm({...form, img: asset.id });
           }).catch((e) => {
             console.error("Copy failed", e);
           });
@@ -28,11 +28,10 @@ const CreatePersona = () => {
         const reader = new FileReader();
         reader.onload = () => {
           const buffer = reader.result as ArrayBuffer;
-          invoke("copy_image_to_persona", {
-            fileName: file.name,
+          invoke<{ id: string }>("store_image_asset", {
             data: Array.from(new Uint8Array(buffer)),
-          }).then(() => {
-            setForm({ ...form, img: `/assets/personas/${file.name}` });
+          }).then((asset) => {
+            setForm({ ...form, img: asset.id });
           });
         };
         reader.readAsArrayBuffer(file);
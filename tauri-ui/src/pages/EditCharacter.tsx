@@ -18,6 +18,8 @@ const EditCharacter = () => {
   const [selectedTags, setSelectedTags] = useState<string[]>([]);
   const [searchTag, setSearchTag] = useState("");
   const [loading, setLoading] = useState(true);
+  // Champs sans formulaire ici (personnalité, salutations, persona par défaut…), conservés tels quels.
+  const [original, setOriginal] = useState<Record<string, unknown>>({});
 
   const predefinedTags = [
     "Anime", "Action", "Adventure", "Fantasy", "Romance", "Shy",
@@ -36,6 +38,7 @@ const EditCharacter = () => {
         .then((characters: any[]) => {
           const character = characters.find(char => char.id === id);
           if (character) {
+            setOriginal(character);
             setForm({
               name: character.name || "",
               tagline: character.tagline || "",
@@ -85,14 +88,11 @@ const EditCharacter = () => {
         const reader = new FileReader();
         reader.onload = () => {
           const buffer = reader.result as ArrayBuffer;
-          const blob = new Blob([buffer]);
-          const newFilePath = `/assets/characters/${file.name}`;
 
-          invoke("copy_image_to_path", {
-            fileName: file.name,
+          invoke<{ id: string }>("store_image_asset", {
             data: Array.from(new Uint8Array(buffer))
-          }).then(() => {
-            setForm({...form, img: newFilePath });
+          }).then((asset) => {
+            setForm({...form, img: asset.id });
           }).catch((e) => {
             console.error("Copy failed", e);
           });
@@ -118,6 +118,7 @@ const EditCharacter = () => {
     }
 
     const character = {
+      ...original,
       id: id!,
       name: form.name,
       tagline: form.tagline,